@@ -0,0 +1,141 @@
+// baseline for the rasterization pipeline, run with `cargo bench` and compare runs with
+// `cargo bench -- --save-baseline before` followed by `cargo bench -- --baseline before`
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use glam::{UVec3, Vec2, Vec3, Vec4};
+use std::path::Path;
+
+use rusterizer::*;
+
+const WIDTH: usize = 640;
+const HEIGHT: usize = 480;
+
+fn edge_function_bench(c: &mut Criterion)
+{
+    let v0 = Vec2::new(12.0, 400.0);
+    let v1 = Vec2::new(600.0, 40.0);
+    let p = Vec2::new(320.5, 240.5);
+
+    c.bench_function("edge_function", |b| b.iter(|| edge_function(black_box(p), black_box(v0), black_box(v1))));
+}
+
+// single triangles already in front of the near plane, so this is the rasterizer on its own;
+// each triangle gets a framebuffer just large enough for it, a fresh one per iteration so the
+// depth test always passes and every covered pixel is shaded
+fn rasterize_bench(c: &mut Criterion)
+{
+    let mut group = c.benchmark_group("rasterize_triangle");
+
+    let cases =
+    [
+        ("small", (64, 64), [Vec2::new(-0.25, -0.25), Vec2::new(0.25, -0.25), Vec2::new(0.0, 0.25)]),
+        ("large", (WIDTH, HEIGHT), [Vec2::new(-0.95, -0.95), Vec2::new(0.95, -0.95), Vec2::new(0.0, 0.95)]),
+        // covers few pixels but its bounding box spans the whole viewport
+        ("sliver", (WIDTH, HEIGHT), [Vec2::new(-0.95, -0.95), Vec2::new(0.95, 0.95), Vec2::new(0.94, 0.95)]),
+    ];
+
+    for (name, (width, height), ndc) in cases
+    {
+        let colors = [Vec3::X, Vec3::Y, Vec3::Z];
+        let triangle = [0, 1, 2].map(|i| Vertex::new(ndc[i].extend(0.5).extend(1.0), Vec3::Z, colors[i], ndc[i]));
+
+        group.bench_function(name, |b| b.iter_batched_ref(
+            || (Framebuffer::with_hdr(width, height), DebugView::new(DebugMode::Shaded, width * height)),
+            |(framebuffer, debug)| clip_and_rasterize_projected_triangle(black_box(triangle), &None, framebuffer, debug, PickId::NONE),
+            BatchSize::LargeInput));
+    }
+
+    group.finish();
+}
+
+fn texture_sampling_bench(c: &mut Criterion)
+{
+    let size = 256;
+    let texture = Texture
+    {
+        width: size,
+        height: size,
+        usage: TextureUsage::Color,
+        data: (0..size * size).map(|i| Vec4::new((i % size) as f32 / size as f32, (i / size) as f32 / size as f32, 0.5, 1.0)).collect(),
+    };
+
+    // a minified, rotated and wrapping walk over the texture
+    let uvs: Vec<Vec2> = (0..1024).map(|i| { let t = i as f32 / 1024.0; Vec2::new(t * 3.7 - 0.5, (t * 40.0).sin() * 1.3) }).collect();
+
+    let mut group = c.benchmark_group("texture");
+    group.throughput(Throughput::Elements(uvs.len() as u64));
+    group.bench_function("sample", |b| b.iter(|| black_box(&uvs).iter().fold(Vec4::ZERO, |sum, uv| sum + texture.sample(*uv))));
+    group.finish();
+}
+
+// whole frames through the renderer, clear and resolve included
+fn frame_bench(c: &mut Criterion)
+{
+    let mut group = c.benchmark_group("frame");
+    group.sample_size(10);
+
+    let camera = Camera::new(std::f32::consts::FRAC_PI_4, WIDTH as f32 / HEIGHT as f32, 0.1, 100.0);
+    let mut renderer = Renderer::new(Framebuffer::with_hdr(WIDTH, HEIGHT));
+
+    let clipped = clipping_scene(200);
+    group.bench_function("clipping", |b| b.iter(|| draw_frame(&mut renderer, &clipped, &Transform::IDENTITY, &camera)));
+
+    let path = Path::new("assets/DamagedHelmet.gltf");
+    if path.exists()
+    {
+        let mut model = load_gltf_model(path);
+        model.mesh.texture = Texture::try_load(Path::new("assets/helmet_albedo.jpg")).ok();
+
+        let mut transform = Transform::from_translation(Vec3::new(0.0, 0.0, -5.0)).mul_transform(&model.transform);
+        transform.scale *= 2.0;
+
+        group.bench_function("helmet", |b| b.iter(|| draw_frame(&mut renderer, &model.mesh, &transform, &camera)));
+    }
+    else
+    {
+        eprintln!("skipping frame/helmet, {} is missing", path.display());
+    }
+
+    group.finish();
+}
+
+fn draw_frame(renderer: &mut Renderer, mesh: &Mesh, transform: &Transform, camera: &Camera)
+{
+    renderer.begin_frame();
+    renderer.draw_mesh(mesh, transform, camera);
+    renderer.end_frame();
+}
+
+// long triangles fanning out from behind the camera, every one of them crosses the near plane,
+// alternating between one and two vertices behind it
+fn clipping_scene(count: usize) -> Mesh
+{
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+    let mut vertices = Vec::with_capacity(count * 3);
+
+    for i in 0..count
+    {
+        let angle = i as f32 * golden_angle;
+        let direction = |offset: f32| Vec2::new((angle + offset).cos(), (angle + offset).sin());
+
+        let behind = (direction(0.0) * 0.5).extend(0.5);
+        let ahead = (direction(0.0) * 3.0).extend(-10.0);
+        let side = (direction(0.05) * 3.0).extend(if i % 2 == 0 { -10.0 } else { 0.5 });
+
+        let color = Vec3::new(i as f32 / count as f32, 0.5, 1.0 - i as f32 / count as f32);
+        vertices.extend([behind, ahead, side].map(|position| Vertex::new(position.extend(1.0), Vec3::Z, color, Vec2::ZERO)));
+    }
+
+    let mut mesh = Mesh
+    {
+        indices: (0..count as u32).map(|i| UVec3::new(i * 3, i * 3 + 1, i * 3 + 2)).collect(),
+        vertices,
+        ..Mesh::new()
+    };
+    mesh.compute_bounds();
+
+    return mesh;
+}
+
+criterion_group!(benches, edge_function_bench, rasterize_bench, texture_sampling_bench, frame_bench);
+criterion_main!(benches);
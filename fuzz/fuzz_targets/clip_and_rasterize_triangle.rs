@@ -0,0 +1,43 @@
+#![no_main]
+
+// arbitrary object space triangles, including nan, infinite and huge coordinates,
+// through the clipper and rasterizer into a small framebuffer
+
+use glam::{Vec2, Vec3};
+use libfuzzer_sys::fuzz_target;
+
+use rusterizer::*;
+
+const WIDTH: usize = 32;
+const HEIGHT: usize = 24;
+
+// beyond this f32 clip coordinates lose too much precision to hold the clipped
+// vertices to the near plane, the rasterizer still has to survive them
+const MAX_COORDINATE: f32 = 1e4;
+
+fuzz_target!(|positions: [[f32; 3]; 3]|
+{
+    let camera = Camera::new(std::f32::consts::FRAC_PI_2, WIDTH as f32 / HEIGHT as f32, 0.1, 100.0);
+    let triangle = positions.map(|position| Vertex::new(Vec3::from(position).extend(1.0), Vec3::Z, Vec3::ONE, Vec2::ZERO));
+
+    let mut framebuffer = Framebuffer::with_hdr(WIDTH, HEIGHT);
+    framebuffer.enable_ids();
+    let mut debug = DebugView::new(DebugMode::Overdraw, WIDTH * HEIGHT);
+
+    let count = clip_and_rasterize_triangle(triangle, camera.projection(), &None, &mut framebuffer, &mut debug, PickId { object: 0, triangle: 0 });
+    assert!(count <= 2);
+
+    if !positions.iter().flatten().all(|c| c.abs() <= MAX_COORDINATE)
+    {
+        return;
+    }
+
+    let projection = camera.projection();
+    let (triangles, count) = clip_triangle(triangle.map(|vertex| Vertex { pos: projection * vertex.pos, ..vertex }));
+
+    for vertex in triangles[..count].iter().flatten()
+    {
+        assert!(vertex.pos.z >= -camera.near * 0.01, "z = {} is behind the near plane", vertex.pos.z);
+        assert!(vertex.pos.w >= camera.near * 0.99, "w = {} is closer than the near plane", vertex.pos.w);
+    }
+});
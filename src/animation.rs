@@ -0,0 +1,262 @@
+use glam::{Quat, Vec3};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Interpolation
+{
+    Step,
+    Linear,
+    CubicSpline,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChannelTarget
+{
+    Translation,
+    Rotation,
+    Scale,
+    MorphWeights,
+}
+
+// a sampled channel value, ready to be written to its node
+#[derive(Clone, Debug)]
+pub enum Property
+{
+    Translation(Vec3),
+    Rotation(Quat),
+    Scale(Vec3),
+    MorphWeights(Vec<f32>),
+}
+
+pub struct Channel
+{
+    // gltf node index the channel animates
+    pub node: usize,
+    pub target: ChannelTarget,
+    pub interpolation: Interpolation,
+    pub times: Vec<f32>,
+    // keyframe values flattened to `stride` floats each, cubic splines store
+    // in tangent, value and out tangent per keyframe
+    pub values: Vec<f32>,
+    pub stride: usize,
+}
+
+impl Channel
+{
+    pub fn sample(&self, time: f32) -> Property
+    {
+        let values = self.sample_values(time);
+
+        return match self.target
+        {
+            ChannelTarget::Translation => Property::Translation(Vec3::from_slice(&values)),
+            ChannelTarget::Rotation => Property::Rotation(Quat::from_slice(&values).normalize()),
+            ChannelTarget::Scale => Property::Scale(Vec3::from_slice(&values)),
+            ChannelTarget::MorphWeights => Property::MorphWeights(values),
+        };
+    }
+
+    fn keyframe(&self, index: usize) -> &[f32]
+    {
+        let index = if self.interpolation == Interpolation::CubicSpline { index * 3 + 1 } else { index };
+        return &self.values[index * self.stride..(index + 1) * self.stride];
+    }
+
+    fn tangent(&self, index: usize, out: bool) -> &[f32]
+    {
+        let index = index * 3 + if out { 2 } else { 0 };
+        return &self.values[index * self.stride..(index + 1) * self.stride];
+    }
+
+    fn sample_values(&self, time: f32) -> Vec<f32>
+    {
+        let last = self.times.len() - 1;
+
+        if self.times.len() == 1 || time <= self.times[0]
+        {
+            return self.keyframe(0).to_vec();
+        }
+
+        if time >= self.times[last]
+        {
+            return self.keyframe(last).to_vec();
+        }
+
+        let next = self.times.partition_point(|t| *t <= time);
+        let prev = next - 1;
+
+        let delta = self.times[next] - self.times[prev];
+        let t = (time - self.times[prev]) / delta;
+
+        match self.interpolation
+        {
+            Interpolation::Step =>
+            {
+                return self.keyframe(prev).to_vec();
+            }
+
+            Interpolation::Linear =>
+            {
+                let a = self.keyframe(prev);
+                let b = self.keyframe(next);
+
+                if self.target == ChannelTarget::Rotation
+                {
+                    return Quat::from_slice(a).slerp(Quat::from_slice(b), t).to_array().to_vec();
+                }
+
+                return a.iter().zip(b.iter()).map(|(a, b)| a + (b - a) * t).collect();
+            }
+
+            Interpolation::CubicSpline =>
+            {
+                let p0 = self.keyframe(prev);
+                let m0 = self.tangent(prev, true);
+                let p1 = self.keyframe(next);
+                let m1 = self.tangent(next, false);
+
+                let t2 = t * t;
+                let t3 = t2 * t;
+
+                let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+                let h10 = t3 - 2.0 * t2 + t;
+                let h01 = -2.0 * t3 + 3.0 * t2;
+                let h11 = t3 - t2;
+
+                return (0..self.stride)
+                .map(|i| h00 * p0[i] + h10 * delta * m0[i] + h01 * p1[i] + h11 * delta * m1[i])
+                .collect();
+            }
+        }
+    }
+}
+
+pub struct AnimationClip
+{
+    pub name: Option<String>,
+    pub channels: Vec<Channel>,
+    pub duration: f32,
+}
+
+impl AnimationClip
+{
+    pub fn load_from_gltf(animation: &gltf::Animation, buffers: &[gltf::buffer::Data]) -> AnimationClip
+    {
+        let mut channels = Vec::new();
+
+        for channel in animation.channels()
+        {
+            let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+
+            let times: Vec<f32> = match reader.read_inputs()
+            {
+                Some(inputs) => inputs.collect(),
+                None => continue,
+            };
+
+            if times.is_empty()
+            {
+                continue;
+            }
+
+            let (target, values, stride) = match reader.read_outputs()
+            {
+                Some(gltf::animation::util::ReadOutputs::Translations(t)) => (ChannelTarget::Translation, t.flatten().collect::<Vec<f32>>(), 3),
+                Some(gltf::animation::util::ReadOutputs::Rotations(r)) => (ChannelTarget::Rotation, r.into_f32().flatten().collect(), 4),
+                Some(gltf::animation::util::ReadOutputs::Scales(s)) => (ChannelTarget::Scale, s.flatten().collect(), 3),
+                Some(gltf::animation::util::ReadOutputs::MorphTargetWeights(w)) =>
+                {
+                    let weights: Vec<f32> = w.into_f32().collect();
+                    let keyframes = if channel.sampler().interpolation() == gltf::animation::Interpolation::CubicSpline { times.len() * 3 } else { times.len() };
+                    let stride = weights.len() / keyframes;
+                    (ChannelTarget::MorphWeights, weights, stride)
+                }
+                None => continue,
+            };
+
+            let interpolation = match channel.sampler().interpolation()
+            {
+                gltf::animation::Interpolation::Step => Interpolation::Step,
+                gltf::animation::Interpolation::Linear => Interpolation::Linear,
+                gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+            };
+
+            channels.push(Channel { node: channel.target().node().index(), target, interpolation, times, values, stride });
+        }
+
+        let duration = channels.iter().map(|c| *c.times.last().unwrap()).fold(0.0, f32::max);
+
+        return AnimationClip { name: animation.name().map(|n| n.to_string()), channels, duration };
+    }
+
+    // a full turn around `axis` every `period` seconds on top of the node's `rest` rotation,
+    // for meshes that ship without animations
+    pub fn turntable(node: usize, axis: Vec3, period: f32, rest: Quat) -> AnimationClip
+    {
+        let keys = 4;
+        let times: Vec<f32> = (0..=keys).map(|i| period * i as f32 / keys as f32).collect();
+        // rest * a spin about the rest-rotated axis, which keeps `axis` upright in the parent's space
+        let values: Vec<f32> = (0..=keys)
+        .flat_map(|i| (Quat::from_axis_angle(axis, std::f32::consts::TAU * i as f32 / keys as f32) * rest).to_array())
+        .collect();
+
+        let channel = Channel { node, target: ChannelTarget::Rotation, interpolation: Interpolation::Linear, times, values, stride: 4 };
+
+        return AnimationClip { name: Some("turntable".to_string()), channels: vec![channel], duration: period };
+    }
+
+    // calls `apply` with the value of every channel at `time`
+    pub fn sample<F: FnMut(usize, Property)>(&self, time: f32, mut apply: F)
+    {
+        for channel in self.channels.iter()
+        {
+            apply(channel.node, channel.sample(time));
+        }
+    }
+}
+
+pub struct AnimationPlayer
+{
+    pub time: f32,
+    pub speed: f32,
+    pub looping: bool,
+    pub playing: bool,
+}
+
+impl Default for AnimationPlayer
+{
+    fn default() -> Self
+    {
+        return Self::new();
+    }
+}
+
+impl AnimationPlayer
+{
+    pub fn new() -> Self
+    {
+        return Self { time: 0.0, speed: 1.0, looping: true, playing: true };
+    }
+
+    pub fn advance(&mut self, dt: f32, clip: &AnimationClip)
+    {
+        if !self.playing
+        {
+            return;
+        }
+
+        self.time += dt * self.speed;
+
+        if clip.duration <= 0.0
+        {
+            self.time = 0.0;
+        }
+        else if self.looping
+        {
+            self.time = self.time.rem_euclid(clip.duration);
+        }
+        else
+        {
+            self.time = self.time.clamp(0.0, clip.duration);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use glam::Vec3;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use rusterizer::*;
+
+const USAGE: &str = "usage: rusterizer-render <model.gltf|model.obj> <output.ppm> [options]
+
+options:
+  --size <width>x<height>      output resolution, default 1280x720
+  --eye <x,y,z>                camera position, default frames the model from +z
+  --target <x,y,z>             point the camera looks at, default the model's center
+  --fov <degrees>              vertical field of view, default 45
+  --near <distance>            near plane, default derived from the model size
+  --far <distance>             far plane, default derived from the model size
+  --mode <mode>                shaded, depth, normals, uvs, overdraw or barycentrics
+  --tonemap <operator>         clamp, reinhard, aces or uncharted2
+  --exposure <stops>           exposure adjustment, default 0
+  --cull <mode>                none, back or front, default none
+  --aa <mode>                  none, msaa2, msaa4, msaa8, ssaa2, ssaa4 or ssaa8, default none
+  --post <effects>             comma separated post-process passes run in order, fxaa or smaa
+  --texture <image>            albedo texture for the model
+  --environment <image>        equirectangular background
+  --stats <file.json>          write the render stats of the frame as json";
+
+struct Options
+{
+    model: PathBuf,
+    output: PathBuf,
+    width: usize,
+    height: usize,
+    eye: Option<Vec3>,
+    target: Option<Vec3>,
+    fov: f32,
+    near: Option<f32>,
+    far: Option<f32>,
+    mode: DebugMode,
+    tonemap: TonemapOperator,
+    exposure: f32,
+    cull_mode: CullMode,
+    antialiasing: Antialiasing,
+    post_process: Vec<PostEffect>,
+    texture: Option<PathBuf>,
+    environment: Option<PathBuf>,
+    stats: Option<PathBuf>,
+}
+
+fn main() -> ExitCode
+{
+    let options = match parse_options(std::env::args().skip(1).collect())
+    {
+        Ok(options) => options,
+        Err(error) =>
+        {
+            eprintln!("{}\n\n{}", error, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    return match render(&options)
+    {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) =>
+        {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    };
+}
+
+fn render(options: &Options) -> Result<(), String>
+{
+    let (mut mesh, transform) = load_model(&options.model)?;
+
+    if let Some(path) = &options.texture
+    {
+        mesh.texture = Some(Texture::try_load(path)?);
+    }
+
+    let environment = match &options.environment
+    {
+        Some(path) => Some(Environment::Equirectangular(Texture::try_load(path)?)),
+        None => None,
+    };
+
+    let camera = frame_camera(options, &mesh, &transform);
+
+    let mut framebuffer = Framebuffer::with_hdr(options.width, options.height);
+    framebuffer.set_antialiasing(options.antialiasing);
+
+    let mut renderer = Renderer::new(framebuffer);
+    renderer.debug.mode = options.mode;
+    renderer.tonemap = Tonemap { operator: options.tonemap, exposure: options.exposure };
+    renderer.state.cull_mode = options.cull_mode;
+    renderer.post_process = PostChain::with_effects(&options.post_process);
+
+    renderer.begin_frame();
+    renderer.draw_mesh(&mesh, &transform, &camera);
+
+    if let Some(environment) = &environment
+    {
+        renderer.draw_background(environment, &camera);
+    }
+
+    renderer.end_frame();
+
+    renderer.framebuffer.save_ppm(&options.output).map_err(|e| format!("{}: {}", options.output.display(), e))?;
+
+    let stats = &renderer.stats;
+
+    if let Some(path) = &options.stats
+    {
+        std::fs::write(path, stats.to_json() + "\n").map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+
+    println!("{} triangles submitted, {} rasterized, wrote {}", stats.triangles_submitted, stats.triangles_rasterized, options.output.display());
+
+    return Ok(());
+}
+
+fn load_model(path: &Path) -> Result<(Mesh, Transform), String>
+{
+    let extension = path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+
+    return match extension.as_deref()
+    {
+        Some("gltf") | Some("glb") =>
+        {
+            let model = try_load_gltf_model(path)?;
+            if model.mesh.vertices.is_empty()
+            {
+                return Err(format!("{}: no mesh found", path.display()));
+            }
+
+            Ok((model.mesh, model.transform))
+        }
+        Some("obj") => Ok((load_obj(path)?, Transform::IDENTITY)),
+        _ => Err(format!("{}: expected a .gltf, .glb or .obj file", path.display())),
+    };
+}
+
+// unless told otherwise, look at the bounding sphere from +z from just far enough to fit it
+fn frame_camera(options: &Options, mesh: &Mesh, transform: &Transform) -> Camera
+{
+    let center = transform.to_matrix().transform_point3(mesh.bounding_sphere.center);
+    let radius = (mesh.bounding_sphere.radius * transform.scale.max_element()).max(1e-3);
+
+    let fov = options.fov.to_radians();
+    let target = options.target.unwrap_or(center);
+    let eye = options.eye.unwrap_or(target + Vec3::Z * (radius / (fov * 0.5).sin() * 1.1));
+
+    let distance = eye.distance(center);
+    let near = options.near.unwrap_or(((distance - radius) * 0.5).max(distance * 1e-3));
+    let far = options.far.unwrap_or(distance + radius * 2.0);
+
+    let mut camera = Camera::new(fov, options.width as f32 / options.height as f32, near, far);
+    camera.position = eye;
+
+    // looking straight up or down needs another up vector
+    let up = if (target - eye).normalize_or_zero().cross(Vec3::Y).length_squared() < 1e-6 { Vec3::Z } else { Vec3::Y };
+    camera.look_at(target, up);
+
+    return camera;
+}
+
+fn parse_options(args: Vec<String>) -> Result<Options, String>
+{
+    let mut positional = Vec::new();
+    let mut options = Options
+    {
+        model: PathBuf::new(),
+        output: PathBuf::new(),
+        width: 1280,
+        height: 720,
+        eye: None,
+        target: None,
+        fov: 45.0,
+        near: None,
+        far: None,
+        mode: DebugMode::Shaded,
+        tonemap: TonemapOperator::AcesFilmic,
+        exposure: 0.0,
+        cull_mode: CullMode::None,
+        antialiasing: Antialiasing::None,
+        post_process: Vec::new(),
+        texture: None,
+        environment: None,
+        stats: None,
+    };
+
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next()
+    {
+        if !arg.starts_with("--")
+        {
+            positional.push(arg);
+            continue;
+        }
+
+        let value = args.next().ok_or(format!("{} needs a value", arg))?;
+        let bad_value = || format!("bad value '{}' for {}", value, arg);
+
+        match arg.as_str()
+        {
+            "--size" =>
+            {
+                let (width, height) = value.split_once('x').ok_or_else(bad_value)?;
+                options.width = width.parse().map_err(|_| bad_value())?;
+                options.height = height.parse().map_err(|_| bad_value())?;
+
+                if options.width == 0 || options.height == 0
+                {
+                    return Err(bad_value());
+                }
+            }
+            "--eye" => options.eye = Some(parse_vec3(&value).ok_or_else(bad_value)?),
+            "--target" => options.target = Some(parse_vec3(&value).ok_or_else(bad_value)?),
+            "--fov" => options.fov = value.parse().map_err(|_| bad_value())?,
+            "--near" => options.near = Some(value.parse().map_err(|_| bad_value())?),
+            "--far" => options.far = Some(value.parse().map_err(|_| bad_value())?),
+            "--mode" => options.mode = *DebugMode::ALL.iter().find(|mode| mode.name() == value).ok_or_else(bad_value)?,
+            "--tonemap" => options.tonemap = *TonemapOperator::ALL.iter().find(|operator| operator.name() == value).ok_or_else(bad_value)?,
+            "--exposure" => options.exposure = value.parse().map_err(|_| bad_value())?,
+            "--cull" =>
+            {
+                options.cull_mode = match value.as_str()
+                {
+                    "none" => CullMode::None,
+                    "back" => CullMode::Back,
+                    "front" => CullMode::Front,
+                    _ => return Err(bad_value()),
+                };
+            }
+            "--post" => options.post_process = value.split(',').map(PostEffect::from_name).collect::<Option<_>>().ok_or_else(bad_value)?,
+            "--aa" => options.antialiasing = Antialiasing::from_name(&value).ok_or_else(bad_value)?,
+            "--texture" => options.texture = Some(PathBuf::from(value)),
+            "--environment" => options.environment = Some(PathBuf::from(value)),
+            "--stats" => options.stats = Some(PathBuf::from(value)),
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+
+    let [model, output]: [String; 2] = positional.try_into().map_err(|_| "expected a model and an output path".to_string())?;
+    options.model = PathBuf::from(model);
+    options.output = PathBuf::from(output);
+
+    // the framebuffer only writes ppm, any other extension would name a file it isn't
+    if !options.output.extension().is_some_and(|e| e.eq_ignore_ascii_case("ppm"))
+    {
+        return Err(format!("{}: expected a .ppm output file", options.output.display()));
+    }
+
+    return Ok(options);
+}
+
+fn parse_vec3(value: &str) -> Option<Vec3>
+{
+    let components: Vec<f32> = value.split(',').map(|c| c.trim().parse().ok()).collect::<Option<_>>()?;
+
+    return if components.len() == 3 { Some(Vec3::new(components[0], components[1], components[2])) } else { None };
+}
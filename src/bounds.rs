@@ -0,0 +1,117 @@
+use glam::{Mat4, Vec3, Vec4};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb
+{
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb
+{
+    // inverted so that growing it by any point gives that point
+    pub const EMPTY: Self = Self { min: Vec3::splat(f32::INFINITY), max: Vec3::splat(f32::NEG_INFINITY) };
+
+    pub fn from_points<I: Iterator<Item = Vec3>>(points: I) -> Self
+    {
+        let mut aabb = Self::EMPTY;
+
+        for point in points
+        {
+            aabb.min = aabb.min.min(point);
+            aabb.max = aabb.max.max(point);
+        }
+
+        return aabb;
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        return self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z;
+    }
+
+    pub fn center(&self) -> Vec3
+    {
+        return (self.min + self.max) * 0.5;
+    }
+
+    pub fn corners(&self) -> [Vec3; 8]
+    {
+        return [
+            Vec3::new(self.min.x, self.min.y, self.min.z),
+            Vec3::new(self.max.x, self.min.y, self.min.z),
+            Vec3::new(self.min.x, self.max.y, self.min.z),
+            Vec3::new(self.max.x, self.max.y, self.min.z),
+            Vec3::new(self.min.x, self.min.y, self.max.z),
+            Vec3::new(self.max.x, self.min.y, self.max.z),
+            Vec3::new(self.min.x, self.max.y, self.max.z),
+            Vec3::new(self.max.x, self.max.y, self.max.z),
+        ];
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere
+{
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere
+{
+    // centered on the box, radius reaching the farthest point
+    pub fn from_points<I: Iterator<Item = Vec3> + Clone>(points: I) -> Self
+    {
+        let center = Aabb::from_points(points.clone()).center();
+        let radius = points.map(|p| p.distance_squared(center)).fold(0.0, f32::max).sqrt();
+
+        return Self { center, radius };
+    }
+}
+
+pub struct Frustum
+{
+    // left, right, bottom, top, near, far with normals pointing inwards
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum
+{
+    // planes end up in the space the matrix transforms from, so a model-view-projection
+    // gives model space planes that can be tested against unskinned mesh bounds directly
+    pub fn from_matrix(matrix: Mat4) -> Self
+    {
+        let r0 = matrix.row(0);
+        let r1 = matrix.row(1);
+        let r2 = matrix.row(2);
+        let r3 = matrix.row(3);
+
+        // glam projections map depth to 0..1, so the near plane is just the z row
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2]
+        .map(|plane| plane / plane.truncate().length().max(f32::EPSILON));
+
+        return Self { planes };
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool
+    {
+        return self.planes.iter().all(|plane| plane.truncate().dot(sphere.center) + plane.w >= -sphere.radius);
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool
+    {
+        for plane in self.planes.iter()
+        {
+            // corner furthest along the plane normal
+            let normal = plane.truncate();
+            let positive = Vec3::select(normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+
+            if normal.dot(positive) + plane.w < 0.0
+            {
+                return false;
+            }
+        }
+
+        return true;
+    }
+}
@@ -0,0 +1,53 @@
+use glam::{Mat4, Vec3, Quat};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Camera
+{
+    pub position: Vec3,
+    // identity looks down -Z with +Y up
+    pub rotation: Quat,
+    // vertical, in radians
+    pub fov: f32,
+    pub aspect: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Camera
+{
+    pub fn new(fov: f32, aspect: f32, near: f32, far: f32) -> Self
+    {
+        return Self { position: Vec3::ZERO, rotation: Quat::IDENTITY, fov, aspect, near, far };
+    }
+
+    pub fn forward(&self) -> Vec3
+    {
+        return self.rotation * Vec3::NEG_Z;
+    }
+
+    pub fn up(&self) -> Vec3
+    {
+        return self.rotation * Vec3::Y;
+    }
+
+    pub fn look_at(&mut self, target: Vec3, up: Vec3)
+    {
+        let view = Mat4::look_at_rh(self.position, target, up);
+        self.rotation = Quat::from_mat4(&view.inverse());
+    }
+
+    pub fn view(&self) -> Mat4
+    {
+        return Mat4::look_to_rh(self.position, self.forward(), self.up());
+    }
+
+    pub fn projection(&self) -> Mat4
+    {
+        return Mat4::perspective_rh(self.fov, self.aspect, self.near, self.far);
+    }
+
+    pub fn view_projection(&self) -> Mat4
+    {
+        return self.projection() * self.view();
+    }
+}
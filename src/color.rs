@@ -0,0 +1,67 @@
+use glam::Vec3;
+use std::sync::OnceLock;
+
+use crate::utils::*;
+
+// resolution of the lookup table used when encoding linear values back to srgb
+const ENCODE_LUT_SIZE: usize = 4096;
+
+pub fn srgb_to_linear(v: f32) -> f32
+{
+    if v <= 0.04045
+    {
+        return v / 12.92;
+    }
+
+    return ((v + 0.055) / 1.055).powf(2.4);
+}
+
+pub fn linear_to_srgb(v: f32) -> f32
+{
+    if v <= 0.0031308
+    {
+        return v * 12.92;
+    }
+
+    return 1.055 * v.powf(1.0 / 2.4) - 0.055;
+}
+
+// 8 bit srgb channel to linear float
+pub fn decode_srgb_u8(v: u8) -> f32
+{
+    static LUT: OnceLock<[f32; 256]> = OnceLock::new();
+
+    let lut = LUT.get_or_init(||
+    {
+        let mut lut = [0.0; 256];
+        for (i, value) in lut.iter_mut().enumerate()
+        {
+            *value = srgb_to_linear(i as f32 / 255.0);
+        }
+        lut
+    });
+
+    return lut[v as usize];
+}
+
+// linear float channel to 8 bit srgb, values outside 0..1 are clamped
+pub fn encode_srgb_u8(v: f32) -> u8
+{
+    static LUT: OnceLock<Vec<u8>> = OnceLock::new();
+
+    let lut = LUT.get_or_init(||
+    {
+        (0..ENCODE_LUT_SIZE)
+        .map(|i| (linear_to_srgb(i as f32 / (ENCODE_LUT_SIZE - 1) as f32) * 255.0 + 0.5) as u8)
+        .collect()
+    });
+
+    let index = (v.clamp(0.0, 1.0) * (ENCODE_LUT_SIZE - 1) as f32 + 0.5) as usize;
+    return lut[index];
+}
+
+// final output conversion for colors computed in linear space
+pub fn from_linear_rgb(color: Vec3) -> u32
+{
+    return from_u8_rgba(encode_srgb_u8(color.x), encode_srgb_u8(color.y), encode_srgb_u8(color.z), 255);
+}
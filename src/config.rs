@@ -0,0 +1,167 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use rusterizer::{Antialiasing, Binding, PostEffect};
+
+// everything the viewer used to hardcode, read from a toml file like viewer.toml
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ViewerConfig
+{
+    // internal render resolution
+    pub width: usize,
+    pub height: usize,
+    // window size over render size, fractional factors are fine
+    pub upscale: f32,
+    // when set, the render resolution drops as low as min_resolution_scale to hold this frame time
+    pub target_frame_ms: Option<f32>,
+    pub min_resolution_scale: f32,
+    // none, msaa2, msaa4, msaa8 or the supersampled ssaa2, ssaa4, ssaa8
+    pub antialiasing: String,
+    // screen space passes run in order over the final image, fxaa or smaa
+    pub post_process: Vec<String>,
+    // optional, the background stays black when it is missing
+    pub environment: Option<PathBuf>,
+    // the textured quad used to show off near plane clipping
+    pub test_quad: bool,
+    #[serde(rename = "model")]
+    pub models: Vec<ModelConfig>,
+    // action name to key or button names, replacing the action's default bindings
+    pub bindings: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ModelConfig
+{
+    // .gltf, .glb or .obj
+    pub path: PathBuf,
+    #[serde(default)]
+    pub texture: Option<PathBuf>,
+    #[serde(default)]
+    pub translation: [f32; 3],
+    #[serde(default = "unit_scale")]
+    pub scale: f32,
+}
+
+fn unit_scale() -> f32
+{
+    return 1.0;
+}
+
+impl Default for ViewerConfig
+{
+    fn default() -> Self
+    {
+        return Self
+        {
+            width: 1920,
+            height: 1080,
+            upscale: 1.0,
+            target_frame_ms: None,
+            min_resolution_scale: 0.5,
+            antialiasing: "none".to_string(),
+            post_process: Vec::new(),
+            environment: Some(PathBuf::from("assets/environment.hdr")),
+            test_quad: true,
+            models: vec![ModelConfig
+            {
+                path: PathBuf::from("assets/DamagedHelmet.gltf"),
+                texture: Some(PathBuf::from("assets/helmet_albedo.jpg")),
+                translation: [0.0, 0.0, -15.0],
+                scale: 2.0,
+            }],
+            bindings: BTreeMap::new(),
+        };
+    }
+}
+
+impl ViewerConfig
+{
+    pub fn load(path: &Path) -> Result<Self, String>
+    {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let config: ViewerConfig = toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        config.validate().map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        return Ok(config);
+    }
+
+    pub fn validate(&self) -> Result<(), String>
+    {
+        if self.width == 0 || self.height == 0
+        {
+            return Err("resolution must not be zero".to_string());
+        }
+
+        if !(self.upscale.is_finite() && self.upscale > 0.0)
+        {
+            return Err(format!("bad upscale factor {}", self.upscale));
+        }
+
+        if !(self.min_resolution_scale > 0.0 && self.min_resolution_scale <= 1.0)
+        {
+            return Err(format!("min_resolution_scale {} is outside (0, 1]", self.min_resolution_scale));
+        }
+
+        if self.target_frame_ms.is_some_and(|ms| !(ms.is_finite() && ms > 0.0))
+        {
+            return Err("target_frame_ms must be positive".to_string());
+        }
+
+        if Antialiasing::from_name(&self.antialiasing).is_none()
+        {
+            return Err(format!("unknown antialiasing mode {}", self.antialiasing));
+        }
+
+        if let Some(name) = self.post_process.iter().find(|name| PostEffect::from_name(name).is_none())
+        {
+            return Err(format!("unknown post-process effect {}", name));
+        }
+
+        for (action, names) in &self.bindings
+        {
+            if let Some(name) = names.iter().find(|name| Binding::from_name(name).is_none())
+            {
+                return Err(format!("unknown key or button {} for {}", name, action));
+            }
+        }
+
+        return Ok(());
+    }
+
+    // validated, so this only falls back to none for configs that never went through validate
+    pub fn antialiasing(&self) -> Antialiasing
+    {
+        return Antialiasing::from_name(&self.antialiasing).unwrap_or(Antialiasing::None);
+    }
+
+    // validated as well, unknown names are skipped
+    pub fn post_process(&self) -> Vec<PostEffect>
+    {
+        return self.post_process.iter().filter_map(|name| PostEffect::from_name(name)).collect();
+    }
+
+    // validated too, unknown names are skipped
+    pub fn bindings(&self) -> Vec<(String, Vec<Binding>)>
+    {
+        return self.bindings.iter().map(|(action, names)| (action.clone(), names.iter().filter_map(|name| Binding::from_name(name)).collect())).collect();
+    }
+
+    // render resolution that fills a window of `window_size` at the configured upscale and a dynamic `scale`
+    pub fn render_size(&self, window_size: (usize, usize), scale: f32) -> (usize, usize)
+    {
+        let size = |window: usize| ((window as f32 / self.upscale * scale).round() as usize).max(1);
+
+        return (size(window_size.0), size(window_size.1));
+    }
+
+    pub fn window_size(&self) -> (usize, usize)
+    {
+        let scale = |size: usize| ((size as f32 * self.upscale).round() as usize).max(1);
+
+        return (scale(self.width), scale(self.height));
+    }
+}
@@ -0,0 +1,132 @@
+use glam::Vec3;
+
+use crate::utils::*;
+
+// number of overlapping fragments that maps to the hot end of the heatmap
+const OVERDRAW_HEATMAP_MAX: u32 = 8;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DebugMode
+{
+    Shaded,
+    Depth,
+    Normals,
+    Uvs,
+    Overdraw,
+    Barycentrics,
+}
+
+impl DebugMode
+{
+    pub const ALL: [DebugMode; 6] =
+    [
+        DebugMode::Shaded,
+        DebugMode::Depth,
+        DebugMode::Normals,
+        DebugMode::Uvs,
+        DebugMode::Overdraw,
+        DebugMode::Barycentrics,
+    ];
+
+    pub fn name(&self) -> &'static str
+    {
+        return match self
+        {
+            DebugMode::Shaded => "shaded",
+            DebugMode::Depth => "depth",
+            DebugMode::Normals => "normals",
+            DebugMode::Uvs => "uvs",
+            DebugMode::Overdraw => "overdraw",
+            DebugMode::Barycentrics => "barycentrics",
+        };
+    }
+}
+
+pub struct DebugView
+{
+    pub mode: DebugMode,
+    pub overdraw: Vec<u32>,
+}
+
+impl DebugView
+{
+    pub fn new(mode: DebugMode, size: usize) -> Self
+    {
+        return Self { mode, overdraw: vec![0; size] };
+    }
+
+    pub fn resize(&mut self, size: usize)
+    {
+        self.overdraw = vec![0; size];
+    }
+
+    pub fn clear(&mut self)
+    {
+        if self.mode == DebugMode::Overdraw
+        {
+            self.overdraw.fill(0);
+        }
+    }
+
+    // depth and overdraw need the whole frame before they can be normalized,
+    // so they are written here once every mesh has been drawn
+    pub fn resolve(&self, buffer: &mut [u32], z_buffer: &[f32])
+    {
+        match self.mode
+        {
+            DebugMode::Depth =>
+            {
+                let mut near = f32::INFINITY;
+                let mut far = 0.0f32;
+
+                for depth in z_buffer.iter().filter(|d| d.is_finite())
+                {
+                    near = near.min(*depth);
+                    far = far.max(*depth);
+                }
+
+                let range = (far - near).max(f32::EPSILON);
+
+                for (pixel, depth) in buffer.iter_mut().zip(z_buffer.iter())
+                {
+                    if depth.is_finite()
+                    {
+                        let v = 1.0 - (depth - near) / range;
+                        *pixel = from_vec3_rgb(Vec3::splat(v));
+                    }
+                }
+            }
+
+            DebugMode::Overdraw =>
+            {
+                for (pixel, count) in buffer.iter_mut().zip(self.overdraw.iter())
+                {
+                    *pixel = if *count == 0 { 0 } else { heatmap(*count as f32 / OVERDRAW_HEATMAP_MAX as f32) };
+                }
+            }
+
+            _ => {}
+        }
+    }
+}
+
+// blue -> green -> yellow -> red
+pub fn heatmap(t: f32) -> u32
+{
+    let t = t.clamp(0.0, 1.0);
+
+    let color = if t < 1.0 / 3.0
+    {
+        Vec3::new(0.0, 0.0, 1.0).lerp(Vec3::new(0.0, 1.0, 0.0), t * 3.0)
+    }
+    else if t < 2.0 / 3.0
+    {
+        Vec3::new(0.0, 1.0, 0.0).lerp(Vec3::new(1.0, 1.0, 0.0), t * 3.0 - 1.0)
+    }
+    else
+    {
+        Vec3::new(1.0, 1.0, 0.0).lerp(Vec3::new(1.0, 0.0, 0.0), t * 3.0 - 2.0)
+    };
+
+    return from_vec3_rgb(color);
+}
@@ -0,0 +1,88 @@
+// raw pixel primitives shared by the text, the hud and the gizmos
+
+pub fn fill_rect(buffer: &mut [u32], viewport: (usize, usize), x: i32, y: i32, width: i32, height: i32, color: u32)
+{
+    let x0 = x.clamp(0, viewport.0 as i32) as usize;
+    let y0 = y.clamp(0, viewport.1 as i32) as usize;
+    let x1 = (x + width).clamp(0, viewport.0 as i32) as usize;
+    let y1 = (y + height).clamp(0, viewport.1 as i32) as usize;
+
+    for py in y0..y1
+    {
+        buffer[py * viewport.0 + x0..py * viewport.0 + x1].fill(color);
+    }
+}
+
+// dda line with square pen of `thickness` pixels, clipped to the viewport
+pub fn draw_line(buffer: &mut [u32], viewport: (usize, usize), from: (f32, f32), to: (f32, f32), thickness: i32, color: u32)
+{
+    // keep the pen's reach past the edges so thick lines leaving the screen still touch them
+    let margin = thickness.max(1) as f32;
+    let min = (-margin, -margin);
+    let max = (viewport.0 as f32 - 1.0 + margin, viewport.1 as f32 - 1.0 + margin);
+
+    let Some((from, to)) = clip_segment(from, to, min, max) else
+    {
+        return;
+    };
+
+    let dx = to.0 - from.0;
+    let dy = to.1 - from.1;
+    let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as i32;
+
+    let offset = thickness / 2;
+
+    for step in 0..=steps
+    {
+        let t = step as f32 / steps as f32;
+        let x = (from.0 + dx * t).round() as i32;
+        let y = (from.1 + dy * t).round() as i32;
+
+        fill_rect(buffer, viewport, x - offset, y - offset, thickness, thickness, color);
+    }
+}
+
+// liang-barsky, the part of the segment inside the rectangle or none when it misses it
+fn clip_segment(from: (f32, f32), to: (f32, f32), min: (f32, f32), max: (f32, f32)) -> Option<((f32, f32), (f32, f32))>
+{
+    if ![from.0, from.1, to.0, to.1].iter().all(|v| v.is_finite())
+    {
+        return None;
+    }
+
+    let dx = to.0 - from.0;
+    let dy = to.1 - from.1;
+
+    let mut t0: f32 = 0.0;
+    let mut t1: f32 = 1.0;
+
+    // each edge as p * t <= q, p < 0 where the segment enters and p > 0 where it leaves
+    for (p, q) in [(-dx, from.0 - min.0), (dx, max.0 - from.0), (-dy, from.1 - min.1), (dy, max.1 - from.1)]
+    {
+        if p == 0.0
+        {
+            if q < 0.0
+            {
+                return None;
+            }
+            continue;
+        }
+
+        let t = q / p;
+        if p < 0.0
+        {
+            t0 = t0.max(t);
+        }
+        else
+        {
+            t1 = t1.min(t);
+        }
+    }
+
+    if t0 > t1
+    {
+        return None;
+    }
+
+    return Some(((from.0 + dx * t0, from.1 + dy * t0), (from.0 + dx * t1, from.1 + dy * t1)));
+}
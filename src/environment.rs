@@ -0,0 +1,126 @@
+use glam::{Mat4, Vec2, Vec3, Vec4, Vec4Swizzles};
+use std::f32::consts::PI;
+use std::path::Path;
+
+use crate::color::*;
+use crate::framebuffer::Framebuffer;
+use crate::texture::Texture;
+
+pub enum Environment
+{
+    Equirectangular(Texture),
+    // faces in +X, -X, +Y, -Y, +Z, -Z order
+    Cubemap(Box<[Texture; 6]>),
+}
+
+impl Environment
+{
+    pub fn load_equirectangular(path: &Path) -> Self
+    {
+        return Self::try_load_equirectangular(path).unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    pub fn try_load_equirectangular(path: &Path) -> Result<Self, String>
+    {
+        return Ok(Environment::Equirectangular(Texture::try_load(path)?));
+    }
+
+    pub fn load_cubemap(paths: [&Path; 6]) -> Self
+    {
+        return Environment::Cubemap(Box::new(paths.map(Texture::load)));
+    }
+
+    // linear radiance seen along a world space direction
+    pub fn sample(&self, direction: Vec3) -> Vec3
+    {
+        let dir = direction.normalize_or_zero();
+
+        match self
+        {
+            Environment::Equirectangular(texture) =>
+            {
+                let u = 0.5 + dir.x.atan2(-dir.z) / (2.0 * PI);
+                let v = dir.y.clamp(-1.0, 1.0).acos() / PI;
+
+                return texture.sample(Vec2::new(u, v)).truncate();
+            }
+
+            Environment::Cubemap(faces) =>
+            {
+                let abs = dir.abs();
+
+                let (face, sc, tc, ma) = if abs.x >= abs.y && abs.x >= abs.z
+                {
+                    if dir.x > 0.0 { (0, -dir.z, -dir.y, abs.x) } else { (1, dir.z, -dir.y, abs.x) }
+                }
+                else if abs.y >= abs.z
+                {
+                    if dir.y > 0.0 { (2, dir.x, dir.z, abs.y) } else { (3, dir.x, -dir.z, abs.y) }
+                }
+                else if dir.z > 0.0
+                {
+                    (4, dir.x, -dir.y, abs.z)
+                }
+                else
+                {
+                    (5, -dir.x, -dir.y, abs.z)
+                };
+
+                let uv = (Vec2::new(sc, tc) / ma.max(f32::EPSILON) + 1.0) * 0.5;
+                let texture = &faces[face];
+
+                // clamp instead of wrapping so face edges don't bleed into each other
+                let uv = uv.clamp(Vec2::ZERO, Vec2::splat(1.0 - 0.5 / texture.width.max(texture.height) as f32));
+
+                return texture.sample(uv).truncate();
+            }
+        }
+    }
+}
+
+// fills every pixel the meshes left at infinite depth with the environment, when multisampling
+// only the samples they left empty, so silhouettes blend into the background on resolve
+pub fn draw_background(framebuffer: &mut Framebuffer, environment: &Environment, view_projection: Mat4)
+{
+    let inverse = view_projection.inverse();
+    let (width, height) = framebuffer.viewport();
+    let sample_count = framebuffer.sample_count();
+
+    for p_i in 0..(width * height)
+    {
+        let depths = &framebuffer.depth[p_i * sample_count..(p_i + 1) * sample_count];
+        if depths.iter().all(|depth| depth.is_finite())
+        {
+            continue;
+        }
+
+        // the rasterizer samples at integer pixel coordinates, so the rays go through those too
+        let x = (p_i % width) as f32 / width as f32 * 2.0 - 1.0;
+        let y = 1.0 - (p_i / width) as f32 / height as f32 * 2.0;
+
+        let near = inverse * Vec4::new(x, y, 0.0, 1.0);
+        let far = inverse * Vec4::new(x, y, 1.0, 1.0);
+        let direction = far.xyz() / far.w - near.xyz() / near.w;
+
+        let radiance = environment.sample(direction);
+
+        if let Some(samples) = &mut framebuffer.samples
+        {
+            for s_i in 0..sample_count
+            {
+                if framebuffer.depth[p_i * sample_count + s_i].is_infinite()
+                {
+                    samples[p_i * sample_count + s_i] = radiance.extend(1.0);
+                }
+            }
+        }
+        else if let Some(hdr) = &mut framebuffer.hdr
+        {
+            hdr[p_i] = radiance.extend(1.0);
+        }
+        else
+        {
+            framebuffer.color[p_i] = from_linear_rgb(radiance);
+        }
+    }
+}
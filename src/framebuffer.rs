@@ -0,0 +1,332 @@
+use glam::{Vec2, Vec4};
+use std::borrow::Cow;
+use std::path::Path;
+
+use crate::color::*;
+use crate::tonemap::Tonemap;
+use crate::picking::PickId;
+use crate::utils::from_vec3_rgb;
+
+// the standard d3d / vulkan sample positions, given in 1/16 pixel around the pixel's sample point
+const fn sample_position(x: f32, y: f32) -> Vec2
+{
+    return Vec2::new(x / 16.0, y / 16.0);
+}
+
+const SAMPLE_PATTERN_1: [Vec2; 1] = [sample_position(0.0, 0.0)];
+const SAMPLE_PATTERN_2: [Vec2; 2] = [sample_position(4.0, 4.0), sample_position(-4.0, -4.0)];
+const SAMPLE_PATTERN_4: [Vec2; 4] = [sample_position(-2.0, -6.0), sample_position(6.0, -2.0), sample_position(-6.0, 2.0), sample_position(2.0, 6.0)];
+const SAMPLE_PATTERN_8: [Vec2; 8] =
+[
+    sample_position(1.0, -3.0), sample_position(-1.0, 3.0), sample_position(5.0, 1.0), sample_position(-3.0, -5.0),
+    sample_position(-5.0, 5.0), sample_position(-7.0, -1.0), sample_position(3.0, 7.0), sample_position(7.0, -7.0),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Antialiasing
+{
+    None,
+    // coverage and depth per sample, shaded once per pixel
+    Msaa(usize),
+    // shaded per sample as well, slow but a good reference
+    Ssaa(usize),
+}
+
+impl Antialiasing
+{
+    pub const ALL: [Antialiasing; 7] =
+    [
+        Antialiasing::None,
+        Antialiasing::Msaa(2),
+        Antialiasing::Msaa(4),
+        Antialiasing::Msaa(8),
+        Antialiasing::Ssaa(2),
+        Antialiasing::Ssaa(4),
+        Antialiasing::Ssaa(8),
+    ];
+
+    pub fn name(&self) -> &'static str
+    {
+        return match self
+        {
+            Antialiasing::None => "none",
+            Antialiasing::Msaa(2) => "msaa2",
+            Antialiasing::Msaa(4) => "msaa4",
+            Antialiasing::Msaa(8) => "msaa8",
+            Antialiasing::Ssaa(2) => "ssaa2",
+            Antialiasing::Ssaa(4) => "ssaa4",
+            Antialiasing::Ssaa(8) => "ssaa8",
+            _ => "unsupported",
+        };
+    }
+
+    pub fn from_name(name: &str) -> Option<Antialiasing>
+    {
+        return Antialiasing::ALL.iter().find(|aa| aa.name() == name).copied();
+    }
+
+    // cycles through the multisampled modes only, supersampling is too slow to toggle into
+    pub fn next(&self) -> Antialiasing
+    {
+        return match self
+        {
+            Antialiasing::None => Antialiasing::Msaa(2),
+            Antialiasing::Msaa(2) => Antialiasing::Msaa(4),
+            Antialiasing::Msaa(4) => Antialiasing::Msaa(8),
+            _ => Antialiasing::None,
+        };
+    }
+
+    pub fn sample_count(&self) -> usize
+    {
+        return match self
+        {
+            Antialiasing::None => 1,
+            Antialiasing::Msaa(count) | Antialiasing::Ssaa(count) => *count,
+        };
+    }
+
+    // offsets from the pixel's sample point, in pixels
+    pub fn sample_pattern(&self) -> &'static [Vec2]
+    {
+        return match self.sample_count()
+        {
+            2 => &SAMPLE_PATTERN_2,
+            4 => &SAMPLE_PATTERN_4,
+            8 => &SAMPLE_PATTERN_8,
+            _ => &SAMPLE_PATTERN_1,
+        };
+    }
+}
+
+pub struct Framebuffer
+{
+    pub width: usize,
+    pub height: usize,
+    pub color: Vec<u32>,
+    // one entry per sample, the samples of a pixel are next to each other
+    pub depth: Vec<f32>,
+    // linear floating point color, when present shaded fragments land here
+    // and only reach `color` through resolve_hdr
+    pub hdr: Option<Vec<Vec4>>,
+    // object and triangle of the nearest fragment per sample, laid out like depth, for picking
+    pub ids: Option<Vec<PickId>>,
+    // per sample colors while multisampling, averaged into hdr or color by resolve_samples;
+    // linear when shaded, display referred for the debug views
+    pub samples: Option<Vec<Vec4>>,
+    antialiasing: Antialiasing,
+}
+
+impl Framebuffer
+{
+    pub fn new(width: usize, height: usize) -> Self
+    {
+        return Self
+        {
+            width,
+            height,
+            color: vec![0; width * height],
+            depth: vec![f32::INFINITY; width * height],
+            hdr: None,
+            ids: None,
+            samples: None,
+            antialiasing: Antialiasing::None,
+        };
+    }
+
+    pub fn with_hdr(width: usize, height: usize) -> Self
+    {
+        let mut framebuffer = Self::new(width, height);
+        framebuffer.hdr = Some(vec![Vec4::ZERO; width * height]);
+
+        return framebuffer;
+    }
+
+    pub fn enable_ids(&mut self)
+    {
+        self.ids = Some(vec![PickId::NONE; self.depth.len()]);
+    }
+
+    // reallocates the depth and sample targets, the contents are lost
+    pub fn set_antialiasing(&mut self, antialiasing: Antialiasing)
+    {
+        assert!(matches!(antialiasing.sample_count(), 1 | 2 | 4 | 8), "unsupported antialiasing {:?}", antialiasing);
+
+        let size = self.width * self.height;
+        let sample_count = antialiasing.sample_count();
+
+        self.antialiasing = antialiasing;
+        self.depth = vec![f32::INFINITY; size * sample_count];
+        self.samples = if sample_count > 1 { Some(vec![Vec4::ZERO; size * sample_count]) } else { None };
+
+        if let Some(ids) = &mut self.ids
+        {
+            *ids = vec![PickId::NONE; size * sample_count];
+        }
+    }
+
+    pub fn antialiasing(&self) -> Antialiasing
+    {
+        return self.antialiasing;
+    }
+
+    pub fn sample_count(&self) -> usize
+    {
+        return self.antialiasing.sample_count();
+    }
+
+    pub fn sample_offsets(&self) -> &'static [Vec2]
+    {
+        return self.antialiasing.sample_pattern();
+    }
+
+    // whether every sample gets shaded on its own instead of once per pixel
+    pub fn sample_shading(&self) -> bool
+    {
+        return matches!(self.antialiasing, Antialiasing::Ssaa(_));
+    }
+
+    // one depth per pixel, the farthest or the nearest of its samples
+    pub fn pixel_depths(&self, farthest: bool) -> Cow<'_, [f32]>
+    {
+        if self.sample_count() == 1
+        {
+            return Cow::Borrowed(&self.depth);
+        }
+
+        let reduce = if farthest { f32::max } else { f32::min };
+        return Cow::Owned(self.depth.chunks_exact(self.sample_count()).map(|samples| samples.iter().copied().reduce(reduce).unwrap()).collect());
+    }
+
+    // the id of the pixel's nearest sample, so a pixel is picked as long as anything covers part of it
+    pub fn id_at(&self, x: usize, y: usize) -> Option<PickId>
+    {
+        if x >= self.width || y >= self.height
+        {
+            return None;
+        }
+
+        let sample_count = self.sample_count();
+        let first = (y * self.width + x) * sample_count;
+
+        let depths = &self.depth[first..first + sample_count];
+        let nearest = (0..sample_count).min_by(|a, b| depths[*a].total_cmp(&depths[*b]))?;
+
+        let id = self.ids.as_ref()?[first + nearest];
+        return if id.is_none() { None } else { Some(id) };
+    }
+
+    // reallocates every target at the new size, the contents are lost
+    pub fn resize(&mut self, width: usize, height: usize)
+    {
+        if (width, height) == (self.width, self.height)
+        {
+            return;
+        }
+
+        let has_ids = self.ids.is_some();
+        let antialiasing = self.antialiasing;
+        *self = if self.hdr.is_some() { Self::with_hdr(width, height) } else { Self::new(width, height) };
+
+        if has_ids
+        {
+            self.enable_ids();
+        }
+
+        self.set_antialiasing(antialiasing);
+    }
+
+    pub fn viewport(&self) -> (usize, usize)
+    {
+        return (self.width, self.height);
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.color.fill(0);
+        self.depth.fill(f32::INFINITY);
+
+        if let Some(hdr) = &mut self.hdr
+        {
+            hdr.fill(Vec4::ZERO);
+        }
+
+        if let Some(ids) = &mut self.ids
+        {
+            ids.fill(PickId::NONE);
+        }
+
+        if let Some(samples) = &mut self.samples
+        {
+            samples.fill(Vec4::ZERO);
+        }
+    }
+
+    // nearest neighbour copy of the displayable colors into a target of any size
+    pub fn resample_into(&self, target: &mut [u32], target_size: (usize, usize))
+    {
+        let (target_width, target_height) = target_size;
+        let columns: Vec<usize> = (0..target_width).map(|x| x * self.width / target_width).collect();
+
+        for (y, row) in target.chunks_exact_mut(target_width).take(target_height).enumerate()
+        {
+            let source_row = &self.color[(y * self.height / target_height) * self.width..][..self.width];
+
+            for (pixel, column) in row.iter_mut().zip(columns.iter())
+            {
+                *pixel = source_row[*column];
+            }
+        }
+    }
+
+    // binary ppm of the displayable colors
+    pub fn save_ppm(&self, path: &Path) -> std::io::Result<()>
+    {
+        let mut data = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        data.reserve(self.color.len() * 3);
+
+        for pixel in &self.color
+        {
+            let [_, red, green, blue] = pixel.to_be_bytes();
+            data.extend_from_slice(&[red, green, blue]);
+        }
+
+        return std::fs::write(path, data);
+    }
+
+    // averages the samples of each pixel into hdr when there is one and the samples are linear,
+    // otherwise straight into the displayable colors
+    pub fn resolve_samples(&mut self, linear: bool)
+    {
+        let Some(samples) = &self.samples else
+        {
+            return;
+        };
+
+        let sample_count = self.antialiasing.sample_count();
+        let weight = 1.0 / sample_count as f32;
+
+        for (p_i, pixel_samples) in samples.chunks_exact(sample_count).enumerate()
+        {
+            let average = pixel_samples.iter().sum::<Vec4>() * weight;
+
+            match &mut self.hdr
+            {
+                Some(hdr) if linear => hdr[p_i] = average,
+                _ if linear => self.color[p_i] = from_linear_rgb(average.truncate()),
+                _ => self.color[p_i] = from_vec3_rgb(average.truncate()),
+            }
+        }
+    }
+
+    pub fn resolve_hdr(&mut self, tonemap: &Tonemap)
+    {
+        if let Some(hdr) = &self.hdr
+        {
+            for (pixel, color) in self.color.iter_mut().zip(hdr.iter())
+            {
+                *pixel = from_linear_rgb(tonemap.apply(color.truncate()));
+            }
+        }
+    }
+}
@@ -1,20 +1,95 @@
-use glam::{Vec2, Vec3, Vec4, UVec3};
+use glam::{Vec2, Vec3, Vec4, UVec3, UVec4};
+use std::borrow::Cow;
 use std::ops::{Add, Mul};
 
 use crate::texture::Texture;
+use crate::skin::Skin;
+use crate::bounds::{Aabb, BoundingSphere};
 
 pub struct Mesh
 {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<UVec3>,
     pub texture: Option<Texture>,
+    pub skin: Option<Skin>,
+    pub morph_targets: Vec<MorphTarget>,
+    // one weight per morph target
+    pub morph_weights: Vec<f32>,
+    // bind pose bounds in model space, see compute_bounds
+    pub aabb: Aabb,
+    pub bounding_sphere: BoundingSphere,
+}
+
+// per vertex displacements, every list is as long as Mesh::vertices
+pub struct MorphTarget
+{
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+}
+
+impl MorphTarget
+{
+    fn resize(&mut self, len: usize)
+    {
+        self.positions.resize(len, Vec3::ZERO);
+        self.normals.resize(len, Vec3::ZERO);
+    }
+}
+
+// area weighted averages of the faces around each vertex whose `has_normal` is false
+pub(crate) fn fill_missing_normals(normals: &mut [Vec3], has_normal: &[bool], positions: &[Vec3], triangles: &[UVec3])
+{
+    if has_normal.iter().all(|has| *has)
+    {
+        return;
+    }
+
+    for triangle in triangles
+    {
+        let [a, b, c] = [triangle.x as usize, triangle.y as usize, triangle.z as usize];
+        let face_normal = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+
+        for v_i in [a, b, c]
+        {
+            if !has_normal[v_i]
+            {
+                normals[v_i] += face_normal;
+            }
+        }
+    }
+
+    for (normal, has) in normals.iter_mut().zip(has_normal.iter())
+    {
+        if !has
+        {
+            *normal = normal.normalize_or_zero();
+        }
+    }
+}
+
+impl Default for Mesh
+{
+    fn default() -> Self
+    {
+        return Self::new();
+    }
 }
 
 impl Mesh
 {
     pub fn new() -> Self
     {
-        return Self { vertices: Vec::new(), indices: Vec::new(), texture: None};
+        return Self
+        {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            texture: None,
+            skin: None,
+            morph_targets: Vec::new(),
+            morph_weights: Vec::new(),
+            aabb: Aabb::EMPTY,
+            bounding_sphere: BoundingSphere { center: Vec3::ZERO, radius: 0.0 },
+        };
     }
 
     // courtesy of Luca
@@ -42,18 +117,40 @@ impl Mesh
             );
             self.vertices.push(vertex);
         }
+
+        self.compute_bounds();
+    }
+
+    // call after editing vertices by hand
+    pub fn compute_bounds(&mut self)
+    {
+        let points = self.vertices.iter().map(|v| v.pos.truncate());
+
+        self.aabb = Aabb::from_points(points.clone());
+        self.bounding_sphere = BoundingSphere::from_points(points);
+    }
+
+    // skinned or morphed meshes can leave their bind pose bounds
+    pub fn is_deformed(&self) -> bool
+    {
+        return self.skin.is_some() || self.morph_weights.iter().any(|w| *w != 0.0);
     }
 
     // courtesy of Luca
-    pub fn load_from_gltf(mesh: &gltf::Mesh, buffers: &[gltf::buffer::Data]) -> Mesh 
+    pub fn load_from_gltf(mesh: &gltf::Mesh, buffers: &[gltf::buffer::Data]) -> Result<Mesh, String>
     {
-        let mut positions: Vec<Vec3> = Vec::new();
-        let mut tex_coords: Vec<Vec2> = Vec::new();
-        let mut normals: Vec<Vec3> = Vec::new();
-        let mut indices = vec![];
-        // TODO: handle errors
         let mut result = Mesh::new();
         for primitive in mesh.primitives() {
+            let mut positions: Vec<Vec3> = Vec::new();
+            let mut tex_coords: Vec<Vec2> = Vec::new();
+            let mut normals: Vec<Vec3> = Vec::new();
+            let mut colors: Vec<Vec3> = Vec::new();
+            let mut joints: Vec<UVec4> = Vec::new();
+            let mut weights: Vec<Vec4> = Vec::new();
+            let mut indices = vec![];
+
+            let base_vertex = result.vertices.len() as u32;
+
             let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
             if let Some(indices_reader) = reader.read_indices() {
                 indices_reader.into_u32().for_each(|i| indices.push(i));
@@ -64,41 +161,168 @@ impl Mesh
             if let Some(normals_reader) = reader.read_normals() {
                 normals_reader.for_each(|n| normals.push(Vec3::new(n[0], n[1], n[2])));
             }
+            if let Some(colors_reader) = reader.read_colors(0) {
+                colors_reader.into_rgb_f32().for_each(|c| colors.push(Vec3::from(c)));
+            }
             if let Some(tex_coord_reader) = reader.read_tex_coords(0) {
                 tex_coord_reader
                     .into_f32()
                     .for_each(|tc| tex_coords.push(Vec2::new(tc[0], tc[1])));
             }
+            if let Some(joints_reader) = reader.read_joints(0) {
+                joints_reader
+                    .into_u16()
+                    .for_each(|j| joints.push(UVec4::new(j[0] as u32, j[1] as u32, j[2] as u32, j[3] as u32)));
+            }
+            if let Some(weights_reader) = reader.read_weights(0) {
+                weights_reader
+                    .into_f32()
+                    .for_each(|w| weights.push(Vec4::from(w)));
+            }
 
-            let colors: Vec<Vec3> = positions.iter().map(|_| Vec3::ONE).collect();
             println!("Num indices: {:?}", indices.len());
             println!("tex_coords: {:?}", tex_coords.len());
             println!("positions: {:?}", positions.len());
 
-            let triangles: Vec<UVec3> = indices
+            // attributes are optional but when present there is one per position
+            let vertex_count = positions.len();
+            let attributes = [("NORMAL", normals.len()), ("COLOR_0", colors.len()), ("TEXCOORD_0", tex_coords.len()), ("JOINTS_0", joints.len()), ("WEIGHTS_0", weights.len())];
+            if let Some((name, count)) = attributes.iter().find(|(_, count)| *count != 0 && *count != vertex_count)
+            {
+                return Err(format!("mesh {}: {} has {} entries for {} positions", mesh.index(), name, count, vertex_count));
+            }
+
+            if let Some(index) = indices.iter().find(|i| **i as usize >= vertex_count)
+            {
+                return Err(format!("mesh {}: index {} is out of range for {} vertices", mesh.index(), index, vertex_count));
+            }
+
+            let mut triangles: Vec<UVec3> = indices
             .chunks_exact(3)
             .map(|tri| UVec3::new(tri[0], tri[1], tri[2]))
             .collect();
+
+            if normals.is_empty()
+            {
+                normals = vec![Vec3::ZERO; vertex_count];
+                fill_missing_normals(&mut normals, &vec![false; vertex_count], &positions, &triangles);
+            }
+
+            // indices are local to the primitive
+            triangles.iter_mut().for_each(|triangle| *triangle += base_vertex);
             result.add_section_from_buffers(&triangles, &positions, &normals, &colors, &tex_coords);
+
+            if !joints.is_empty() && !weights.is_empty()
+            {
+                for (i, vertex) in result.vertices[base_vertex as usize..].iter_mut().enumerate()
+                {
+                    vertex.joints = joints[i];
+                    vertex.weights = weights[i];
+                }
+            }
+
+            // tangent displacements are skipped, vertices have no tangents to move
+            for (t_i, (target_positions, target_normals, _)) in reader.read_morph_targets().enumerate()
+            {
+                if result.morph_targets.len() <= t_i
+                {
+                    result.morph_targets.push(MorphTarget { positions: Vec::new(), normals: Vec::new() });
+                }
+
+                let target = &mut result.morph_targets[t_i];
+                target.resize(base_vertex as usize);
+
+                if let Some(displacements) = target_positions
+                {
+                    target.positions.extend(displacements.map(Vec3::from));
+                }
+                if let Some(displacements) = target_normals
+                {
+                    target.normals.extend(displacements.map(Vec3::from));
+                }
+            }
+
+            let vertex_count = result.vertices.len();
+            result.morph_targets.iter_mut().for_each(|t| t.resize(vertex_count));
+        }
+
+        result.morph_weights = match mesh.weights()
+        {
+            Some(weights) => weights.to_vec(),
+            None => vec![0.0; result.morph_targets.len()],
+        };
+        result.morph_weights.resize(result.morph_targets.len(), 0.0);
+
+        return Ok(result);
+    }
+
+    // extra weights are ignored, missing ones count as zero
+    pub fn set_morph_weights(&mut self, weights: &[f32])
+    {
+        for (i, weight) in self.morph_weights.iter_mut().enumerate()
+        {
+            *weight = weights.get(i).copied().unwrap_or(0.0);
+        }
+    }
+
+    // vertices after morph targets and then the skin have been applied,
+    // borrowed as is for static meshes
+    pub fn deformed_vertices(&self) -> Cow<'_, [Vertex]>
+    {
+        let active_targets: Vec<(&MorphTarget, f32)> = self.morph_targets.iter()
+        .zip(self.morph_weights.iter())
+        .filter(|(_, w)| **w != 0.0)
+        .map(|(t, w)| (t, *w))
+        .collect();
+
+        if active_targets.is_empty() && self.skin.is_none()
+        {
+            return Cow::Borrowed(&self.vertices);
         }
-        return result;
+
+        let vertices = self.vertices.iter().enumerate()
+        .map(|(i, vertex)|
+        {
+            let mut vertex = *vertex;
+
+            if !active_targets.is_empty()
+            {
+                for (target, weight) in active_targets.iter()
+                {
+                    vertex.pos += (target.positions[i] * *weight).extend(0.0);
+                    vertex.normal += target.normals[i] * *weight;
+                }
+                vertex.normal = vertex.normal.normalize_or_zero();
+            }
+
+            match &self.skin
+            {
+                Some(skin) => skin.skin_vertex(&vertex),
+                None => vertex,
+            }
+        })
+        .collect();
+
+        return Cow::Owned(vertices);
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct Vertex
 {
     pub pos: Vec4,
     pub normal: Vec3,
-    pub color: Vec3,
+    pub color: Vec3, // linear rgb
     pub uv: Vec2,
+    pub joints: UVec4,
+    pub weights: Vec4,
 }
 
 impl Vertex
 {
     pub fn new(position: Vec4, normal: Vec3, color: Vec3, uv: Vec2) -> Self 
     {
-        return Self { pos: position, normal: normal, color: color, uv: uv };
+        return Self { pos: position, normal, color, uv, joints: UVec4::ZERO, weights: Vec4::ZERO };
     }
 }
 
@@ -112,8 +336,9 @@ impl Mul<f32> for Vertex
         let normal = self.normal * rhs;
         let color = self.color * rhs;
         let uv = self.uv * rhs;
+        let weights = self.weights * rhs;
 
-        return Self { pos: position, normal: normal, color: color, uv: uv };
+        return Self { pos: position, normal, color, uv, joints: self.joints, weights };
     }
 }
 
@@ -125,9 +350,10 @@ impl Add for Vertex
     {
         let position = self.pos + rhs.pos;
         let normal = self.normal + rhs.normal;
-        let color = self.color + self.color;
+        let color = self.color + rhs.color;
         let uv = self.uv + rhs.uv;
+        let weights = self.weights + rhs.weights;
 
-        return Self { pos: position, normal: normal, color: color, uv: uv };
+        return Self { pos: position, normal, color, uv, joints: self.joints, weights };
     }
 }
\ No newline at end of file
@@ -0,0 +1,468 @@
+use glam::{Quat, Vec2, Vec3, Vec4Swizzles};
+
+use crate::camera::Camera;
+use crate::input::{MouseState, PointState};
+use crate::picking::Ray;
+use crate::draw::{draw_line, fill_rect};
+use crate::transform::Transform;
+use crate::utils::*;
+
+// axis length as a fraction of the viewport height, kept constant on screen
+const GIZMO_SCREEN_SIZE: f32 = 0.12;
+// how close in pixels the mouse has to be to grab a handle
+const HANDLE_PICK_DISTANCE: f32 = 6.0;
+const HANDLE_SIZE: i32 = 9;
+const LINE_THICKNESS: i32 = 2;
+const RING_SEGMENTS: usize = 48;
+// plane handles are squares between two axes, in fractions of the axis length
+const PLANE_HANDLE_OFFSET: f32 = 0.25;
+const PLANE_HANDLE_SIZE: f32 = 0.2;
+
+const AXES: [Vec3; 3] = [Vec3::X, Vec3::Y, Vec3::Z];
+const AXIS_COLORS: [u32; 3] = [0xFFE04040, 0xFF40C040, 0xFF4060E0];
+const ACTIVE_COLOR: u32 = 0xFFFFD020;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GizmoMode
+{
+    Translate,
+    Rotate,
+    Scale,
+}
+
+// axes are in world space, indices 0-2 are x, y, z
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GizmoHandle
+{
+    // move or scale along the axis, rotate around it
+    Axis(usize),
+    // move within the plane perpendicular to the axis
+    Plane(usize),
+    // scale all axes together
+    Uniform,
+}
+
+#[derive(Clone, Copy)]
+struct Drag
+{
+    handle: GizmoHandle,
+    start_transform: Transform,
+    // where the handle was grabbed, meaning depends on the handle and mode
+    start_point: Vec3,
+    start_mouse: Vec2,
+}
+
+// screen space manipulator for a single transform, interacts like input::MoveablePoint
+pub struct Gizmo
+{
+    pub mode: GizmoMode,
+    state: PointState,
+    hovered: Option<GizmoHandle>,
+    drag: Option<Drag>,
+}
+
+impl Default for Gizmo
+{
+    fn default() -> Self
+    {
+        return Self::new();
+    }
+}
+
+impl Gizmo
+{
+    pub fn new() -> Gizmo
+    {
+        return Gizmo { mode: GizmoMode::Translate, state: PointState::None, hovered: None, drag: None };
+    }
+
+    // hovered or held, the mouse belongs to the gizmo
+    pub fn is_active(&self) -> bool
+    {
+        return self.state != PointState::None;
+    }
+
+    pub fn active_handle(&self) -> Option<GizmoHandle>
+    {
+        return self.drag.map(|drag| drag.handle).or(self.hovered);
+    }
+
+    pub fn set_mode(&mut self, mode: GizmoMode)
+    {
+        if self.state != PointState::Held
+        {
+            self.mode = mode;
+        }
+    }
+
+    pub fn update(&mut self, transform: &mut Transform, camera: &Camera, viewport: (usize, usize), mouse: &mut MouseState)
+    {
+        match self.state
+        {
+            PointState::None =>
+            {
+                self.hovered = self.hit_test(mouse.pos, transform, camera, viewport);
+                if self.hovered.is_some()
+                {
+                    self.state = PointState::Hovered;
+                }
+            }
+
+            PointState::Hovered =>
+            {
+                self.hovered = self.hit_test(mouse.pos, transform, camera, viewport);
+                let Some(handle) = self.hovered else
+                {
+                    self.state = PointState::None;
+                    return;
+                };
+
+                if mouse.left_button && !mouse.has_selected
+                {
+                    let ray = camera.screen_ray(mouse.pos, viewport);
+
+                    if let Some(start_point) = self.grab_point(handle, &ray, transform.translation)
+                    {
+                        self.drag = Some(Drag { handle, start_transform: *transform, start_point, start_mouse: mouse.pos });
+                        self.state = PointState::Held;
+                        mouse.has_selected = true;
+                    }
+                }
+            }
+
+            PointState::Held =>
+            {
+                if !mouse.left_button
+                {
+                    self.drag = None;
+                    self.state = PointState::Hovered;
+                    mouse.has_selected = false;
+                }
+                else if let Some(drag) = self.drag
+                {
+                    self.apply_drag(&drag, transform, camera, viewport, mouse.pos);
+                }
+            }
+        }
+    }
+
+    pub fn draw(&self, buffer: &mut [u32], viewport: (usize, usize), transform: &Transform, camera: &Camera)
+    {
+        let origin = transform.translation;
+        let length = axis_length(camera, origin);
+        let Some(center) = project(camera, viewport, origin) else { return };
+
+        let active = self.active_handle();
+        let color = |handle: GizmoHandle, base: u32| if active == Some(handle) { ACTIVE_COLOR } else { base };
+
+        match self.mode
+        {
+            GizmoMode::Translate | GizmoMode::Scale =>
+            {
+                if self.mode == GizmoMode::Translate
+                {
+                    for (axis, base_color) in AXIS_COLORS.iter().enumerate()
+                    {
+                        if let Some(quad) = plane_handle(camera, viewport, origin, axis, length)
+                        {
+                            let plane_color = color(GizmoHandle::Plane(axis), *base_color);
+                            for c_i in 0..4
+                            {
+                                draw_line(buffer, viewport, quad[c_i].into(), quad[(c_i + 1) % 4].into(), 1, plane_color);
+                            }
+                        }
+                    }
+                }
+
+                for axis in 0..3
+                {
+                    let Some(tip) = project(camera, viewport, origin + AXES[axis] * length) else { continue };
+                    let axis_color = color(GizmoHandle::Axis(axis), AXIS_COLORS[axis]);
+
+                    draw_line(buffer, viewport, center.into(), tip.into(), LINE_THICKNESS, axis_color);
+
+                    if self.mode == GizmoMode::Translate
+                    {
+                        draw_arrow_head(buffer, viewport, center, tip, axis_color);
+                    }
+                    else
+                    {
+                        fill_rect(buffer, viewport, tip.x as i32 - HANDLE_SIZE / 2, tip.y as i32 - HANDLE_SIZE / 2, HANDLE_SIZE, HANDLE_SIZE, axis_color);
+                    }
+                }
+
+                if self.mode == GizmoMode::Scale
+                {
+                    fill_rect(buffer, viewport, center.x as i32 - HANDLE_SIZE / 2, center.y as i32 - HANDLE_SIZE / 2, HANDLE_SIZE, HANDLE_SIZE, color(GizmoHandle::Uniform, 0xFFE0E0E0));
+                }
+            }
+
+            GizmoMode::Rotate =>
+            {
+                for (axis, base_color) in AXIS_COLORS.iter().enumerate()
+                {
+                    let ring = ring_points(camera, viewport, origin, axis, length);
+                    let ring_color = color(GizmoHandle::Axis(axis), *base_color);
+
+                    for p_i in 0..ring.len()
+                    {
+                        if let (Some(a), Some(b)) = (ring[p_i], ring[(p_i + 1) % ring.len()])
+                        {
+                            draw_line(buffer, viewport, a.into(), b.into(), LINE_THICKNESS, ring_color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // closest handle under the mouse, plane and uniform handles win over axes since they sit on top
+    fn hit_test(&self, mouse: Vec2, transform: &Transform, camera: &Camera, viewport: (usize, usize)) -> Option<GizmoHandle>
+    {
+        let origin = transform.translation;
+        let length = axis_length(camera, origin);
+        let center = project(camera, viewport, origin)?;
+
+        match self.mode
+        {
+            GizmoMode::Translate =>
+            {
+                for axis in 0..3
+                {
+                    if let Some(quad) = plane_handle(camera, viewport, origin, axis, length)
+                    {
+                        if inside_quad(mouse, &quad)
+                        {
+                            return Some(GizmoHandle::Plane(axis));
+                        }
+                    }
+                }
+            }
+
+            GizmoMode::Scale =>
+            {
+                if (mouse - center).abs().max_element() <= HANDLE_SIZE as f32 * 0.5 + 1.0
+                {
+                    return Some(GizmoHandle::Uniform);
+                }
+            }
+
+            GizmoMode::Rotate =>
+            {
+                let mut closest: Option<(f32, usize)> = None;
+
+                for axis in 0..3
+                {
+                    let ring = ring_points(camera, viewport, origin, axis, length);
+
+                    for p_i in 0..ring.len()
+                    {
+                        if let (Some(a), Some(b)) = (ring[p_i], ring[(p_i + 1) % ring.len()])
+                        {
+                            let distance = distance_to_segment(mouse, a, b);
+                            if distance <= HANDLE_PICK_DISTANCE && closest.is_none_or(|(d, _)| distance < d)
+                            {
+                                closest = Some((distance, axis));
+                            }
+                        }
+                    }
+                }
+
+                return closest.map(|(_, axis)| GizmoHandle::Axis(axis));
+            }
+        }
+
+        let mut closest: Option<(f32, usize)> = None;
+
+        for (axis, direction) in AXES.iter().enumerate()
+        {
+            let Some(tip) = project(camera, viewport, origin + *direction * length) else { continue };
+
+            let distance = distance_to_segment(mouse, center, tip);
+            if distance <= HANDLE_PICK_DISTANCE && closest.is_none_or(|(d, _)| distance < d)
+            {
+                closest = Some((distance, axis));
+            }
+        }
+
+        return closest.map(|(_, axis)| GizmoHandle::Axis(axis));
+    }
+
+    // world space anchor of the drag under `ray`, None when the ray runs parallel to the constraint
+    fn grab_point(&self, handle: GizmoHandle, ray: &Ray, origin: Vec3) -> Option<Vec3>
+    {
+        return match (self.mode, handle)
+        {
+            (GizmoMode::Translate | GizmoMode::Scale, GizmoHandle::Axis(axis)) => closest_on_axis(ray, origin, AXES[axis]).map(|s| origin + AXES[axis] * s),
+            (_, GizmoHandle::Plane(axis)) => intersect_plane(ray, origin, AXES[axis]),
+            // rotation and uniform scale work off the mouse position alone
+            (GizmoMode::Rotate, GizmoHandle::Axis(_)) | (_, GizmoHandle::Uniform) => Some(origin),
+        };
+    }
+
+    fn apply_drag(&self, drag: &Drag, transform: &mut Transform, camera: &Camera, viewport: (usize, usize), mouse: Vec2)
+    {
+        let start = drag.start_transform;
+        let origin = start.translation;
+        let ray = camera.screen_ray(mouse, viewport);
+
+        match (self.mode, drag.handle)
+        {
+            (GizmoMode::Translate, GizmoHandle::Axis(_) | GizmoHandle::Plane(_)) =>
+            {
+                if let Some(point) = self.grab_point(drag.handle, &ray, origin)
+                {
+                    transform.translation = origin + (point - drag.start_point);
+                }
+            }
+
+            (GizmoMode::Rotate, GizmoHandle::Axis(axis)) =>
+            {
+                // angle swept around the projected center, rings seen edge on can still be turned this way
+                if let Some(center) = project(camera, viewport, origin)
+                {
+                    let from = drag.start_mouse - center;
+                    let to = mouse - center;
+                    let screen_angle = from.perp_dot(to).atan2(from.dot(to));
+
+                    // screen y points down, so a clockwise sweep is a positive angle around an axis facing the camera
+                    let facing = AXES[axis].dot(camera.position - origin) > 0.0;
+                    let angle = if facing { -screen_angle } else { screen_angle };
+
+                    transform.rotation = (Quat::from_axis_angle(AXES[axis], angle) * start.rotation).normalize();
+                }
+            }
+
+            (GizmoMode::Scale, GizmoHandle::Axis(axis)) =>
+            {
+                let start_offset = (drag.start_point - origin).dot(AXES[axis]);
+
+                if let (Some(point), true) = (self.grab_point(drag.handle, &ray, origin), start_offset.abs() > f32::EPSILON)
+                {
+                    let factor = (point - origin).dot(AXES[axis]) / start_offset;
+                    transform.scale[axis] = start.scale[axis] * factor;
+                }
+            }
+
+            (GizmoMode::Scale, GizmoHandle::Uniform) =>
+            {
+                // no axis to project on, horizontal mouse travel scales instead
+                let factor = (1.0 + (mouse.x - drag.start_mouse.x) / (viewport.1 as f32 * GIZMO_SCREEN_SIZE)).max(0.01);
+                transform.scale = start.scale * factor;
+            }
+
+            _ => {}
+        }
+    }
+}
+
+// world space length that covers GIZMO_SCREEN_SIZE of the screen at `origin`
+fn axis_length(camera: &Camera, origin: Vec3) -> f32
+{
+    let depth = (origin - camera.position).dot(camera.forward()).max(camera.near);
+
+    return depth * (camera.fov * 0.5).tan() * 2.0 * GIZMO_SCREEN_SIZE;
+}
+
+// pixel coordinates as used by the rasterizer, None behind the near plane
+fn project(camera: &Camera, viewport: (usize, usize), point: Vec3) -> Option<Vec2>
+{
+    let clip = camera.view_projection() * point.extend(1.0);
+    if clip.w < camera.near
+    {
+        return None;
+    }
+
+    let ndc = clip.xy() / clip.w;
+
+    return Some(Vec2::new((ndc.x + 1.0) * 0.5 * viewport.0 as f32, (1.0 - ndc.y) * 0.5 * viewport.1 as f32));
+}
+
+// None when the plane is seen almost edge on and the handle would collapse onto the axes
+fn plane_handle(camera: &Camera, viewport: (usize, usize), origin: Vec3, axis: usize, length: f32) -> Option<[Vec2; 4]>
+{
+    if AXES[axis].dot((origin - camera.position).normalize_or_zero()).abs() < 0.2
+    {
+        return None;
+    }
+
+    let u = AXES[(axis + 1) % 3] * length;
+    let v = AXES[(axis + 2) % 3] * length;
+    let near = PLANE_HANDLE_OFFSET;
+    let far = PLANE_HANDLE_OFFSET + PLANE_HANDLE_SIZE;
+
+    return Some(
+    [
+        project(camera, viewport, origin + u * near + v * near)?,
+        project(camera, viewport, origin + u * far + v * near)?,
+        project(camera, viewport, origin + u * far + v * far)?,
+        project(camera, viewport, origin + u * near + v * far)?,
+    ]);
+}
+
+fn ring_points(camera: &Camera, viewport: (usize, usize), origin: Vec3, axis: usize, length: f32) -> Vec<Option<Vec2>>
+{
+    let u = AXES[(axis + 1) % 3] * length;
+    let v = AXES[(axis + 2) % 3] * length;
+
+    return (0..RING_SEGMENTS).map(|s_i|
+    {
+        let angle = s_i as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+        project(camera, viewport, origin + u * angle.cos() + v * angle.sin())
+    }).collect();
+}
+
+fn draw_arrow_head(buffer: &mut [u32], viewport: (usize, usize), from: Vec2, tip: Vec2, color: u32)
+{
+    let direction = (tip - from).normalize_or_zero();
+    let side = direction.perp();
+    let base = tip - direction * HANDLE_SIZE as f32;
+
+    draw_line(buffer, viewport, tip.into(), (base + side * HANDLE_SIZE as f32 * 0.5).into(), LINE_THICKNESS, color);
+    draw_line(buffer, viewport, tip.into(), (base - side * HANDLE_SIZE as f32 * 0.5).into(), LINE_THICKNESS, color);
+}
+
+fn distance_to_segment(p: Vec2, a: Vec2, b: Vec2) -> f32
+{
+    let ab = b - a;
+    let t = if ab.length_squared() > 0.0 { ((p - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0) } else { 0.0 };
+
+    return p.distance(a + ab * t);
+}
+
+// convex quad in either winding
+fn inside_quad(p: Vec2, quad: &[Vec2; 4]) -> bool
+{
+    let signs: Vec<f32> = (0..4).map(|c_i| edge_function(p, quad[c_i], quad[(c_i + 1) % 4])).collect();
+
+    return signs.iter().all(|s| *s >= 0.0) || signs.iter().all(|s| *s <= 0.0);
+}
+
+// parameter along the (unit) axis of the point closest to the ray
+fn closest_on_axis(ray: &Ray, origin: Vec3, axis: Vec3) -> Option<f32>
+{
+    let w = origin - ray.origin;
+    let b = axis.dot(ray.direction);
+    let denominator = 1.0 - b * b;
+
+    // looking straight down the axis, any point is as good as any other
+    if denominator < 1e-4
+    {
+        return None;
+    }
+
+    return Some((b * ray.direction.dot(w) - axis.dot(w)) / denominator);
+}
+
+fn intersect_plane(ray: &Ray, origin: Vec3, normal: Vec3) -> Option<Vec3>
+{
+    let denominator = normal.dot(ray.direction);
+    if denominator.abs() < 1e-4
+    {
+        return None;
+    }
+
+    let t = normal.dot(origin - ray.origin) / denominator;
+
+    return if t >= 0.0 { Some(ray.at(t)) } else { None };
+}
@@ -0,0 +1,110 @@
+// max-depth pyramid over the z-buffer, each texel holds the farthest depth of
+// the pixels below it so anything nearer than that can't be ruled out
+pub struct DepthPyramid
+{
+    pub levels: Vec<DepthLevel>,
+}
+
+pub struct DepthLevel
+{
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<f32>,
+}
+
+impl Default for DepthPyramid
+{
+    fn default() -> Self
+    {
+        return Self::new();
+    }
+}
+
+impl DepthPyramid
+{
+    pub fn new() -> Self
+    {
+        return Self { levels: Vec::new() };
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        return self.levels.is_empty();
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.levels.clear();
+    }
+
+    pub fn build(&mut self, depth: &[f32], width: usize, height: usize)
+    {
+        self.levels.clear();
+        self.levels.push(DepthLevel { width, height, data: depth.to_vec() });
+
+        while let Some(previous) = self.levels.last()
+        {
+            if previous.width == 1 && previous.height == 1
+            {
+                break;
+            }
+
+            let level_width = previous.width.div_ceil(2);
+            let level_height = previous.height.div_ceil(2);
+            let mut data = vec![0.0; level_width * level_height];
+
+            for y in 0..level_height
+            {
+                for x in 0..level_width
+                {
+                    let x0 = x * 2;
+                    let y0 = y * 2;
+                    let x1 = (x0 + 1).min(previous.width - 1);
+                    let y1 = (y0 + 1).min(previous.height - 1);
+
+                    data[y * level_width + x] = previous.data[y0 * previous.width + x0]
+                        .max(previous.data[y0 * previous.width + x1])
+                        .max(previous.data[y1 * previous.width + x0])
+                        .max(previous.data[y1 * previous.width + x1]);
+                }
+            }
+
+            self.levels.push(DepthLevel { width: level_width, height: level_height, data });
+        }
+    }
+
+    // true if every pixel in the inclusive rect already holds something nearer than `nearest_depth`
+    pub fn is_occluded(&self, min: (usize, usize), max: (usize, usize), nearest_depth: f32) -> bool
+    {
+        if self.levels.is_empty()
+        {
+            return false;
+        }
+
+        // finest level where the rect touches at most 2x2 texels
+        let mut level = 0;
+        while level + 1 < self.levels.len() && ((max.0 >> level) - (min.0 >> level) > 1 || (max.1 >> level) - (min.1 >> level) > 1)
+        {
+            level += 1;
+        }
+
+        let texels = &self.levels[level];
+        let x0 = (min.0 >> level).min(texels.width - 1);
+        let x1 = (max.0 >> level).min(texels.width - 1);
+        let y0 = (min.1 >> level).min(texels.height - 1);
+        let y1 = (max.1 >> level).min(texels.height - 1);
+
+        for y in y0..=y1
+        {
+            for x in x0..=x1
+            {
+                if texels.data[y * texels.width + x] >= nearest_depth
+                {
+                    return false;
+                }
+            }
+        }
+
+        return true;
+    }
+}
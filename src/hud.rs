@@ -0,0 +1,80 @@
+use glam::Vec3;
+
+use rusterizer::*;
+use rusterizer::draw::fill_rect;
+use rusterizer::text::text_size;
+
+const HUD_SCALE: usize = 2;
+const HUD_MARGIN: i32 = 8;
+
+pub struct Hud
+{
+    pub visible: bool,
+    // the per-stage wall clock times, off when the frames have to be reproducible
+    pub show_timings: bool,
+    frame_time: f32,
+}
+
+impl Hud
+{
+    pub fn new() -> Self
+    {
+        return Self { visible: true, show_timings: true, frame_time: 0.0 };
+    }
+
+    // call once per frame with the seconds it took, smoothed so the numbers stay readable
+    pub fn tick(&mut self, elapsed: f32)
+    {
+        self.frame_time = if self.frame_time == 0.0 { elapsed } else { self.frame_time * 0.9 + elapsed * 0.1 };
+    }
+
+    pub fn fps(&self) -> f32
+    {
+        return if self.frame_time > 0.0 { 1.0 / self.frame_time } else { 0.0 };
+    }
+
+    pub fn draw(&self, buffer: &mut [u32], viewport: (usize, usize), stats: &RenderStats, eye: Vec3, extra: &str)
+    {
+        if !self.visible
+        {
+            return;
+        }
+
+        let mut text = format!(
+            "FPS {:.1}\nFrame {:.2} ms\nMeshes {} drawn {} culled {} occluded\nVerts {} processed\nTris {} submitted\nTris {} culled {} occluded\nClip {} away {} cut {} split\nTris {} rasterized\nPixels {} tested {} passed\nCamera {:.1} {:.1} {:.1}",
+            self.fps(),
+            self.frame_time * 1000.0,
+            stats.meshes_drawn,
+            stats.meshes_culled,
+            stats.meshes_occluded,
+            stats.vertices_processed,
+            stats.triangles_submitted,
+            stats.triangles_culled,
+            stats.triangles_occluded,
+            stats.triangles_clipped_away,
+            stats.triangles_clipped,
+            stats.triangles_split,
+            stats.triangles_rasterized,
+            stats.pixels_tested,
+            stats.pixels_passed,
+            eye.x, eye.y, eye.z,
+        );
+
+        if self.show_timings
+        {
+            let timings: Vec<String> = StageTimings::NAMES.iter().zip(stats.timings.stages()).map(|(name, time)| format!("{} {:.2}", name, time.as_secs_f32() * 1000.0)).collect();
+            text += &format!("\nStage ms {}\nStage ms {}", timings[..3].join(" "), timings[3..].join(" "));
+        }
+
+        if !extra.is_empty()
+        {
+            text += "\n";
+            text += extra;
+        }
+
+        let (width, height) = text_size(&text, HUD_SCALE);
+        fill_rect(buffer, viewport, 0, 0, width as i32 + HUD_MARGIN * 2, height as i32 + HUD_MARGIN * 2, from_u8_rgba(16, 16, 16, 255));
+
+        draw_text_scaled(buffer, viewport, HUD_MARGIN, HUD_MARGIN, HUD_SCALE, from_u8_rgba(255, 255, 255, 255), &text);
+    }
+}
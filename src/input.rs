@@ -1,5 +1,5 @@
 use glam::{Vec2, Vec3, Vec3Swizzles};
-use minifb::Window;
+use minifb::{Key, MouseButton, Window};
 
 use crate::utils::*;
 
@@ -21,7 +21,7 @@ impl MouseState
 }
 
 #[derive(Clone, PartialEq, Eq)]
-enum PointState
+pub(crate) enum PointState
 {
     None,
     Hovered,
@@ -35,6 +35,14 @@ pub struct MoveablePoint
     state: PointState,
 }
 
+impl Default for MoveablePoint
+{
+    fn default() -> Self
+    {
+        return Self::new();
+    }
+}
+
 impl MoveablePoint
 {
     pub fn new() -> MoveablePoint
@@ -83,4 +91,249 @@ impl MoveablePoint
             }
         }
     }
-}
\ No newline at end of file
+}
+
+// every key a config can name, matched by the variant's debug name
+const KEYS: [Key; 106] =
+[
+    Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+    Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12, Key::F13, Key::F14, Key::F15,
+    Key::Down, Key::Left, Key::Right, Key::Up, Key::Apostrophe, Key::Backquote,
+    Key::Backslash, Key::Comma, Key::Equal, Key::LeftBracket, Key::Minus, Key::Period, Key::RightBracket, Key::Semicolon,
+    Key::Slash, Key::Backspace, Key::Delete, Key::End, Key::Enter, Key::Escape, Key::Home, Key::Insert, Key::Menu,
+    Key::PageDown, Key::PageUp, Key::Pause, Key::Space, Key::Tab, Key::NumLock, Key::CapsLock, Key::ScrollLock,
+    Key::LeftShift, Key::RightShift, Key::LeftCtrl, Key::RightCtrl,
+    Key::NumPad0, Key::NumPad1, Key::NumPad2, Key::NumPad3, Key::NumPad4, Key::NumPad5, Key::NumPad6, Key::NumPad7, Key::NumPad8, Key::NumPad9,
+    Key::NumPadDot, Key::NumPadSlash, Key::NumPadAsterisk, Key::NumPadMinus, Key::NumPadPlus, Key::NumPadEnter,
+    Key::LeftAlt, Key::RightAlt, Key::LeftSuper, Key::RightSuper,
+];
+
+const MOUSE_BUTTONS: [(MouseButton, &str); 3] = [(MouseButton::Left, "MouseLeft"), (MouseButton::Middle, "MouseMiddle"), (MouseButton::Right, "MouseRight")];
+
+pub fn key_from_name(name: &str) -> Option<Key>
+{
+    return KEYS.iter().find(|key| format!("{:?}", key) == name).copied();
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Binding
+{
+    Key(Key),
+    Mouse(MouseButton),
+}
+
+impl Binding
+{
+    // key names as in minifb::Key, plus MouseLeft, MouseMiddle and MouseRight
+    pub fn from_name(name: &str) -> Option<Binding>
+    {
+        if let Some((button, _)) = MOUSE_BUTTONS.iter().find(|(_, button_name)| *button_name == name)
+        {
+            return Some(Binding::Mouse(*button));
+        }
+
+        return key_from_name(name).map(Binding::Key);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Modifiers
+{
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+// raw device state for a single frame
+#[derive(Clone, Default, Debug)]
+pub struct InputFrame
+{
+    pub keys: Vec<Key>,
+    pub mouse_pos: Vec2,
+    // left, middle, right
+    pub mouse_buttons: [bool; 3],
+    pub wheel: f32,
+}
+
+impl InputFrame
+{
+    // mouse position is mapped from the window to a framebuffer of `viewport` size
+    pub fn capture(window: &Window, viewport: (usize, usize)) -> InputFrame
+    {
+        let (x, y) = window.get_mouse_pos(minifb::MouseMode::Clamp).unwrap_or((0.0, 0.0));
+        let (window_width, window_height) = window.get_size();
+        let scale = Vec2::new(viewport.0 as f32 / window_width.max(1) as f32, viewport.1 as f32 / window_height.max(1) as f32);
+
+        let mut frame = InputFrame
+        {
+            keys: window.get_keys(),
+            mouse_pos: Vec2::new(x, y) * scale,
+            mouse_buttons: MOUSE_BUTTONS.map(|(button, _)| window.get_mouse_down(button)),
+            wheel: window.get_scroll_wheel().map_or(0.0, |(_, y)| y),
+        };
+        frame.retain_known_keys();
+
+        return frame;
+    }
+
+    // drops Key::Unknown and anything else a config or recording couldn't name
+    pub fn retain_known_keys(&mut self)
+    {
+        self.keys.retain(|key| KEYS.contains(key));
+    }
+
+    pub fn is_down(&self, binding: &Binding) -> bool
+    {
+        return match binding
+        {
+            Binding::Key(key) => self.keys.contains(key),
+            Binding::Mouse(button) => MOUSE_BUTTONS.iter().position(|(b, _)| b == button).is_some_and(|b_i| self.mouse_buttons[b_i]),
+        };
+    }
+}
+
+// named actions and the keys or buttons that trigger them
+#[derive(Clone, Default, Debug)]
+pub struct InputMap
+{
+    actions: Vec<(String, Vec<Binding>)>,
+}
+
+impl InputMap
+{
+    pub fn new() -> InputMap
+    {
+        return InputMap { actions: Vec::new() };
+    }
+
+    pub fn bind(&mut self, action: &str, binding: Binding)
+    {
+        match self.actions.iter_mut().find(|(name, _)| name == action)
+        {
+            Some((_, bindings)) => bindings.push(binding),
+            None => self.actions.push((action.to_string(), vec![binding])),
+        }
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding]
+    {
+        return self.actions.iter().find(|(name, _)| name == action).map_or(&[], |(_, bindings)| bindings.as_slice());
+    }
+
+    // replaces whatever `action` was bound to
+    pub fn rebind(&mut self, action: &str, bindings: Vec<Binding>)
+    {
+        self.actions.retain(|(name, _)| name != action);
+        self.actions.push((action.to_string(), bindings));
+    }
+}
+
+// seconds before a held action starts repeating and between repeats, the minifb defaults
+const KEY_REPEAT_DELAY: f32 = 0.25;
+const KEY_REPEAT_RATE: f32 = 0.05;
+
+// action state with edges against the previous frame
+pub struct Input
+{
+    pub map: InputMap,
+    current: InputFrame,
+    previous: InputFrame,
+    // how long each binding that is down has been held, for repeats
+    held: Vec<(Binding, f32)>,
+    dt: f32,
+}
+
+impl Input
+{
+    pub fn new(map: InputMap) -> Input
+    {
+        return Input { map, current: InputFrame::default(), previous: InputFrame::default(), held: Vec::new(), dt: 0.0 };
+    }
+
+    // `dt` is the time since the previous frame
+    pub fn update(&mut self, frame: InputFrame, dt: f32)
+    {
+        self.previous = std::mem::replace(&mut self.current, frame);
+        self.dt = dt;
+
+        let previous_held = std::mem::take(&mut self.held);
+        for binding in self.map.actions.iter().flat_map(|(_, bindings)| bindings)
+        {
+            if !self.current.is_down(binding) || self.held.iter().any(|(b, _)| b == binding)
+            {
+                continue;
+            }
+
+            let time = previous_held.iter().find(|(b, _)| b == binding).map_or(0.0, |(_, time)| time + dt);
+            self.held.push((*binding, time));
+        }
+    }
+
+    pub fn frame(&self) -> &InputFrame
+    {
+        return &self.current;
+    }
+
+    pub fn is_down(&self, action: &str) -> bool
+    {
+        return self.map.bindings(action).iter().any(|binding| self.current.is_down(binding));
+    }
+
+    // true on the first frame the action is held
+    pub fn is_pressed(&self, action: &str) -> bool
+    {
+        return self.is_down(action) && !self.map.bindings(action).iter().any(|binding| self.previous.is_down(binding));
+    }
+
+    // like is_pressed, then again every KEY_REPEAT_RATE once the action is held longer than KEY_REPEAT_DELAY
+    pub fn is_pressed_repeat(&self, action: &str) -> bool
+    {
+        let repeats = |time: f32| if time < KEY_REPEAT_DELAY { -1.0 } else { ((time - KEY_REPEAT_DELAY) / KEY_REPEAT_RATE).floor() };
+
+        return self.is_pressed(action) || self.map.bindings(action).iter().any(|binding|
+        {
+            self.held.iter().find(|(b, _)| b == binding).is_some_and(|(_, time)| repeats(*time) > repeats(time - self.dt))
+        });
+    }
+
+    // true on the first frame the action is let go
+    pub fn is_released(&self, action: &str) -> bool
+    {
+        return !self.is_down(action) && self.map.bindings(action).iter().any(|binding| self.previous.is_down(binding));
+    }
+
+    pub fn mouse_pos(&self) -> Vec2
+    {
+        return self.current.mouse_pos;
+    }
+
+    pub fn mouse_delta(&self) -> Vec2
+    {
+        return self.current.mouse_pos - self.previous.mouse_pos;
+    }
+
+    pub fn wheel(&self) -> f32
+    {
+        return self.current.wheel;
+    }
+
+    pub fn modifiers(&self) -> Modifiers
+    {
+        let down = |keys: [Key; 2]| keys.iter().any(|key| self.current.keys.contains(key));
+
+        return Modifiers
+        {
+            shift: down([Key::LeftShift, Key::RightShift]),
+            ctrl: down([Key::LeftCtrl, Key::RightCtrl]),
+            alt: down([Key::LeftAlt, Key::RightAlt]),
+        };
+    }
+
+    // the mouse as seen by MoveablePoint and the gizmos, with `action` standing in for the left button
+    pub fn mouse_state(&self, action: &str, item_selected: bool) -> MouseState
+    {
+        return MouseState { pos: self.current.mouse_pos, left_button: self.is_down(action), has_selected: item_selected };
+    }
+}
@@ -1,22 +1,62 @@
-use glam::{Mat4, Vec3, Vec2, UVec2};
+use glam::{Mat4, Vec3, Vec2, UVec2, Quat};
 use minifb::clamp;
 use std::path::Path;
-use std::cmp::{min, max};
 
 pub mod input;
+pub mod gizmo;
+pub mod recording;
 pub mod geometry;
+pub mod obj;
+pub mod skin;
+pub mod animation;
+pub mod transform;
+pub mod camera;
+pub mod stats;
+pub mod bounds;
+pub mod hiz;
+pub mod picking;
+pub mod renderer;
+pub mod resolution;
 pub mod utils;
 pub mod texture;
+pub mod debug;
+pub mod color;
+pub mod tonemap;
+pub mod postprocess;
+pub mod framebuffer;
+pub mod environment;
+pub mod text;
+pub mod draw;
 pub use 
 {
-    input::MouseState,
+    debug::{DebugMode, DebugView},
+    text::{draw_text, draw_text_scaled},
+    input::{MouseState, Input, InputMap, InputFrame, Binding, Modifiers},
+    gizmo::{Gizmo, GizmoMode, GizmoHandle},
+    recording::InputRecording,
     geometry::Mesh,
-    geometry::Vertex,
-    texture::Texture,
+    geometry::{Vertex, MorphTarget},
+    obj::load_obj,
+    skin::{Skin, Joint},
+    animation::{AnimationClip, AnimationPlayer, Property},
+    transform::Transform,
+    camera::Camera,
+    stats::{RenderStats, StageTimings},
+    bounds::{Aabb, BoundingSphere, Frustum},
+    hiz::DepthPyramid,
+    picking::{PickId, Ray, RayHit, pick},
+    renderer::{Renderer, PipelineState, CullMode},
+    resolution::DynamicResolution,
+    texture::{Texture, TextureUsage},
+    color::{srgb_to_linear, linear_to_srgb, from_linear_rgb},
+    tonemap::{Tonemap, TonemapOperator},
+    postprocess::{PostChain, PostEffect},
+    framebuffer::{Framebuffer, Antialiasing},
+    environment::{Environment, draw_background},
     utils::*,
 };
 
-pub fn clip_and_rasterize_triangle(triangle_original: [Vertex; 3], mvp: Mat4, texture: &Option<Texture>, buffer: &mut Vec<u32>, z_buffer: &mut Vec<f32>, viewport: (usize, usize))
+pub fn clip_and_rasterize_triangle(triangle_original: [Vertex; 3], mvp: Mat4, texture: &Option<Texture>, framebuffer: &mut Framebuffer, debug: &mut DebugView, id: PickId) -> usize
 {
     let mut triangle_projected = triangle_original;
 
@@ -24,6 +64,26 @@ pub fn clip_and_rasterize_triangle(triangle_original: [Vertex; 3], mvp: Mat4, te
     triangle_projected[1].pos = mvp * triangle_original[1].pos;
     triangle_projected[2].pos = mvp * triangle_original[2].pos;
 
+    return clip_and_rasterize_projected_triangle(triangle_projected, texture, framebuffer, debug, id);
+}
+
+// same as clip_and_rasterize_triangle for vertices already in clip space
+pub fn clip_and_rasterize_projected_triangle(triangle_projected: [Vertex; 3], texture: &Option<Texture>, framebuffer: &mut Framebuffer, debug: &mut DebugView, id: PickId) -> usize
+{
+    let (triangles, count) = clip_triangle(triangle_projected);
+
+    for triangle in &triangles[..count]
+    {
+        rasterize_triangle(*triangle, texture, framebuffer, debug, id);
+    }
+
+    return count;
+}
+
+// cuts a clip space triangle against the near plane (z >= 0), returns up to two triangles
+// and how many of them are valid, attributes of new vertices are interpolated along the cut edges
+pub fn clip_triangle(triangle_projected: [Vertex; 3]) -> ([[Vertex; 3]; 2], usize)
+{
     let mut new_order = [0; 3];
     let mut num_valid = 0;
     let mut num_invalid = 0;
@@ -48,74 +108,75 @@ pub fn clip_and_rasterize_triangle(triangle_original: [Vertex; 3], mvp: Mat4, te
 
     match num_invalid 
     {
-        3 => return,
+        3 => return ([triangle_ordered; 2], 0),
 
         2 => 
         {
-            let alpha01 = -triangle_ordered[0].pos.z / (triangle_ordered[1].pos.z - triangle_ordered[0].pos.z);
-            let alpha02 = -triangle_ordered[0].pos.z / (triangle_ordered[2].pos.z - triangle_ordered[0].pos.z);
-
-            let prime1 = triangle_ordered[0] * (1.0 - alpha01) + triangle_ordered[1] * alpha01;
-            let prime2 = triangle_ordered[0] * (1.0 - alpha02) + triangle_ordered[2] * alpha02;
+            let prime1 = intersect_near_plane(triangle_ordered[0], triangle_ordered[1]);
+            let prime2 = intersect_near_plane(triangle_ordered[0], triangle_ordered[2]);
 
             let mut triangle_local = triangle_ordered;
 
             triangle_local[1] = prime1;
             triangle_local[2] = prime2;
 
-            // visual testing
-            let red = Vec3::new(1.0, 0.0, 0.0);
-
-            triangle_local[0].color = red;
-            triangle_local[1].color = red;
-            triangle_local[2].color = red;
-
-            rasterize_triangle(triangle_local,  texture, buffer, z_buffer, viewport)
+            return ([triangle_local; 2], 1);
         }
 
         1 =>
         {
-            let alpha02 = -triangle_ordered[0].pos.z / (triangle_ordered[2].pos.z - triangle_ordered[0].pos.z);
-            let alpha12 = -triangle_ordered[1].pos.z / (triangle_ordered[2].pos.z - triangle_ordered[1].pos.z);
-
-            let prime0 = triangle_ordered[0] * (1.0 - alpha02) + triangle_ordered[2] * alpha02;
-            let prime1 = triangle_ordered[1] * (1.0 - alpha12) + triangle_ordered[2] * alpha12;
-
-            let mut tri0 = [triangle_ordered[0], triangle_ordered[1], prime0];
-            let mut tri1 = [prime0, triangle_ordered[1], prime1];
-
-            // visual testing
-            let green = Vec3::new(0.0, 1.0, 0.0);
-            let blue = Vec3::new(0.0, 0.0, 1.0);
+            let prime0 = intersect_near_plane(triangle_ordered[0], triangle_ordered[2]);
+            let prime1 = intersect_near_plane(triangle_ordered[1], triangle_ordered[2]);
 
-            tri0[0].color = green;
-            tri0[1].color = green;
-            tri0[2].color = green;
+            let tri0 = [triangle_ordered[0], triangle_ordered[1], prime0];
+            let tri1 = [prime0, triangle_ordered[1], prime1];
 
-            tri1[0].color = blue;
-            tri1[1].color = blue;
-            tri1[2].color = blue;
-            
-            rasterize_triangle(tri0, texture, buffer, z_buffer, viewport);
-            rasterize_triangle(tri1, texture, buffer, z_buffer, viewport);
+            return ([tri0, tri1], 2);
         }
 
         0 =>
         {
-            rasterize_triangle(triangle_ordered, texture, buffer, z_buffer, viewport);
+            return ([triangle_ordered; 2], 1);
         }
 
         _ =>
         {
-
+            return ([triangle_ordered; 2], 0);
         }
     }
+}
+
+// where the edge from a to b crosses z = 0, measured from the end closer to the plane,
+// otherwise a very distant vertex rounds the short distance to the plane away
+fn intersect_near_plane(a: Vertex, b: Vertex) -> Vertex
+{
+    if a.pos.z.abs() > b.pos.z.abs()
+    {
+        return intersect_near_plane(b, a);
+    }
+
+    let alpha = -a.pos.z / (b.pos.z - a.pos.z);
+
+    return a * (1.0 - alpha) + b * alpha;
+}
 
-    
+// returns how many covered pixels were depth tested and how many of them passed, samples when multisampling
+fn rasterize_triangle(triangle: [Vertex; 3], texture: &Option<Texture>, framebuffer: &mut Framebuffer, debug: &mut DebugView, id: PickId) -> (usize, usize)
+{
+    // one copy per sample count so the sample loop unrolls and single sampling costs nothing extra
+    return match framebuffer.sample_count()
+    {
+        2 => rasterize_triangle_samples::<2>(triangle, texture, framebuffer, debug, id),
+        4 => rasterize_triangle_samples::<4>(triangle, texture, framebuffer, debug, id),
+        8 => rasterize_triangle_samples::<8>(triangle, texture, framebuffer, debug, id),
+        _ => rasterize_triangle_samples::<1>(triangle, texture, framebuffer, debug, id),
+    };
 }
 
-fn rasterize_triangle(triangle: [Vertex; 3], texture: &Option<Texture>, buffer: &mut Vec<u32>, z_buffer: &mut Vec<f32>, viewport: (usize, usize))
+fn rasterize_triangle_samples<const SAMPLES: usize>(triangle: [Vertex; 3], texture: &Option<Texture>, framebuffer: &mut Framebuffer, debug: &mut DebugView, id: PickId) -> (usize, usize)
 {
+    let viewport = framebuffer.viewport();
+
     let rec0 = 1.0 / triangle[0].pos.w;
     let rec1 = 1.0 / triangle[1].pos.w;
     let rec2 = 1.0 / triangle[2].pos.w;
@@ -127,6 +188,7 @@ fn rasterize_triangle(triangle: [Vertex; 3], texture: &Option<Texture>, buffer:
     let v0 = triangle[0] * rec0;
     let v1 = triangle[1] * rec1;
     let v2 = triangle[2] * rec2;
+    let vertices = [v0, v1, v2];
 
     let sc0 = Vec2::new(map_to_range(ndc0.x, -1.0, 1.0, 0.0, viewport.0 as f32), map_to_range(-ndc0.y, -1.0, 1.0, 0.0, viewport.1 as f32));
     let sc1 = Vec2::new(map_to_range(ndc1.x, -1.0, 1.0, 0.0, viewport.0 as f32), map_to_range(-ndc1.y, -1.0, 1.0, 0.0, viewport.1 as f32));
@@ -134,18 +196,27 @@ fn rasterize_triangle(triangle: [Vertex; 3], texture: &Option<Texture>, buffer:
 
     let area = edge_function(sc0, sc1, sc2);
 
-    let clamped_sc0 = UVec2::new(clamp(0, sc0.x as i32, viewport.0 as i32 - 1) as u32, clamp(0, sc0.y as i32, viewport.1 as i32 - 1) as u32);
-    let clamped_sc1 = UVec2::new(clamp(0, sc1.x as i32, viewport.0 as i32 - 1) as u32, clamp(0, sc1.y as i32, viewport.1 as i32 - 1) as u32);
-    let clamped_sc2 = UVec2::new(clamp(0, sc2.x as i32, viewport.0 as i32 - 1) as u32, clamp(0, sc2.y as i32, viewport.1 as i32 - 1) as u32);
+    let offsets: &[Vec2; SAMPLES] = framebuffer.sample_offsets().try_into().unwrap();
+    // constant false for a single sample, which keeps per sample shading out of that copy's loop
+    let sample_shading = SAMPLES > 1 && framebuffer.sample_shading();
 
-    let lower_bounds = UVec2::new(min(min(clamped_sc0.x, clamped_sc1.x), clamped_sc2.x), 
-                                         min(min(clamped_sc0.y, clamped_sc1.y), clamped_sc2.y));
-    let upper_bounds = UVec2::new(max(max(clamped_sc0.x, clamped_sc1.x), clamped_sc2.x), 
-                                         max(max(clamped_sc0.y, clamped_sc1.y), clamped_sc2.y));
+    // samples sit up to this far from their pixel's sample point, so pixels just outside the triangle can still be covered
+    let reach = offsets.iter().fold(0.0f32, |reach, offset| reach.max(offset.abs().max_element()));
+
+    let clamped_lower = |sc: Vec2| UVec2::new(clamp(0, (sc.x - reach) as i32, viewport.0 as i32 - 1) as u32, clamp(0, (sc.y - reach) as i32, viewport.1 as i32 - 1) as u32);
+    let clamped_upper = |sc: Vec2| UVec2::new(clamp(0, (sc.x + reach) as i32, viewport.0 as i32 - 1) as u32, clamp(0, (sc.y + reach) as i32, viewport.1 as i32 - 1) as u32);
+
+    let lower_bounds = clamped_lower(sc0).min(clamped_lower(sc1)).min(clamped_lower(sc2));
+    let upper_bounds = clamped_upper(sc0).max(clamped_upper(sc1)).max(clamped_upper(sc2));
 
     let width = upper_bounds.x as usize - lower_bounds.x as usize + 1;                 
     let height = upper_bounds.y as usize - lower_bounds.y as usize + 1;
 
+    let linear = debug.mode == DebugMode::Shaded;
+
+    let mut pixels_tested = 0;
+    let mut pixels_passed = 0;
+
     for frame_pixel_i in 0..(width * height)
     {
         let local_coords = (frame_pixel_i % width, frame_pixel_i / width);
@@ -153,60 +224,221 @@ fn rasterize_triangle(triangle: [Vertex; 3], texture: &Option<Texture>, buffer:
         let p_i = coords.1 * viewport.0 + coords.0;
         let point = Vec2::new(coords.0 as f32, coords.1 as f32);
 
-        let a = edge_function(point, sc1, sc2) / area;
-        let b = edge_function(point, sc2, sc0) / area;
-        let c = edge_function(point, sc0, sc1) / area;
+        // coverage and depth are tested per sample, the samples that pass share one shaded color
+        // unless the framebuffer is supersampling
+        let mut passed_mask = 0u32;
+        let mut first_covered = None;
 
-        if a >= 0.0 && b >= 0.0 && c >= 0.0
+        for (s_i, offset) in offsets.iter().enumerate()
         {
-            let correction = a * rec0 + b * rec1 + c * rec2;
-                        
-            let correction = 1.0 / correction;
-            let depth = correction;
+            let sample = point + *offset;
 
-            if depth < z_buffer[p_i]
+            let a = edge_function(sample, sc1, sc2) / area;
+            let b = edge_function(sample, sc2, sc0) / area;
+            let c = edge_function(sample, sc0, sc1) / area;
+
+            if a >= 0.0 && b >= 0.0 && c >= 0.0
             {
-                z_buffer[p_i] = depth;
+                // perspective correction and linear depth are the same thing
+                let barycentric = Vec3::new(a, b, c);
+                let depth = 1.0 / (a * rec0 + b * rec1 + c * rec2);
+                first_covered.get_or_insert((barycentric, depth));
+
+                let d_i = p_i * SAMPLES + s_i;
 
-                let color;
-                       
-                if let Some(texture) = texture
+                pixels_tested += 1;
+
+                if depth < framebuffer.depth[d_i]
                 {
-                    let mut uv = v0.uv * a;
-                    uv += v1.uv * b;
-                    uv += v2.uv * c;
-                    uv *= correction;
-
-                    //uv.x = clamp(0.0, uv.x, 1.0);
-                    //uv.y = clamp(0.0, uv.y, 1.0);
-
-                    let texture_x = (uv.x * texture.width as f32) as usize % texture.width;
-                    let texture_y = (uv.y * texture.height as f32) as usize % texture.height;
-                    
-                    let texture_index = texture_x + texture_y * texture.width;
-                    color = texture.data[clamp(0, texture_index, texture.data.len() - 1)];
+                    pixels_passed += 1;
+                    framebuffer.depth[d_i] = depth;
+                    passed_mask |= 1 << s_i;
+
+                    if sample_shading
+                    {
+                        let color = fragment_color(&vertices, barycentric, depth, texture, debug.mode);
+                        write_fragment::<SAMPLES>(framebuffer, p_i, 1 << s_i, color, linear);
+                    }
                 }
-                else 
-                {
-                    let mut rgb = v0.color * a;
-                    rgb += v1.color * b;
-                    rgb += v2.color * c;
-                    rgb *= correction;
+            }
+        }
+
+        let Some(first_covered) = first_covered else
+        {
+            continue;
+        };
 
-                    color = from_u8_rgba((rgb.x * 255.0) as u8, (rgb.y * 255.0) as u8, (rgb.z * 255.0) as u8, 255);
+        if debug.mode == DebugMode::Overdraw
+        {
+            debug.overdraw[p_i] += 1;
+        }
+
+        if passed_mask == 0
+        {
+            continue;
+        }
+
+        // ids are kept per sample like depth, so edge pixels still pick whatever covers them
+        if let Some(ids) = &mut framebuffer.ids
+        {
+            for s_i in (0..SAMPLES).filter(|s_i| passed_mask & (1 << s_i) != 0)
+            {
+                ids[p_i * SAMPLES + s_i] = id;
+            }
+        }
+
+        if !sample_shading
+        {
+            // shade at the pixel's sample point when the triangle covers it, otherwise at a
+            // covered sample so attributes are not extrapolated past the edge
+            let (mut barycentric, mut correction) = first_covered;
+
+            if SAMPLES > 1
+            {
+                let center = Vec3::new(edge_function(point, sc1, sc2), edge_function(point, sc2, sc0), edge_function(point, sc0, sc1)) / area;
+                if center.min_element() >= 0.0
+                {
+                    barycentric = center;
+                    correction = 1.0 / center.dot(Vec3::new(rec0, rec1, rec2));
                 }
+            }
+
+            let color = fragment_color(&vertices, barycentric, correction, texture, debug.mode);
+            write_fragment::<SAMPLES>(framebuffer, p_i, passed_mask, color, linear);
+        }
+    }
+
+    return (pixels_tested, pixels_passed);
+}
+
+// vertices pre-divided by w, linear rgb when shaded and display referred for the debug views
+#[inline(always)]
+fn fragment_color(vertices: &[Vertex; 3], barycentric: Vec3, correction: f32, texture: &Option<Texture>, mode: DebugMode) -> Option<Vec3>
+{
+    if mode == DebugMode::Shaded
+    {
+        return Some(shade_fragment(vertices, barycentric, correction, texture));
+    }
+
+    return debug_fragment_color(vertices, barycentric, correction, mode);
+}
+
+// kept out of line so the debug views don't slow down the rasterizer loop, None for the views
+// DebugView::resolve fills in once the frame is complete
+#[inline(never)]
+fn debug_fragment_color(vertices: &[Vertex; 3], barycentric: Vec3, correction: f32, mode: DebugMode) -> Option<Vec3>
+{
+    let [v0, v1, v2] = vertices;
+    let (a, b, c) = (barycentric.x, barycentric.y, barycentric.z);
+
+    return match mode
+    {
+        DebugMode::Normals =>
+        {
+            let normal = (v0.normal * a + v1.normal * b + v2.normal * c) * correction;
+            Some(normal.normalize_or_zero() * 0.5 + 0.5)
+        }
+
+        DebugMode::Uvs =>
+        {
+            let uv = (v0.uv * a + v1.uv * b + v2.uv * c) * correction;
+            Some(uv.fract().extend(0.0))
+        }
+
+        DebugMode::Barycentrics => Some(barycentric),
+
+        DebugMode::Shaded | DebugMode::Depth | DebugMode::Overdraw => None,
+    };
+}
 
-                buffer[p_i] = color;
+// stores a fragment in the samples of `mask`, or straight in the pixel without multisampling
+fn write_fragment<const SAMPLES: usize>(framebuffer: &mut Framebuffer, p_i: usize, mask: u32, color: Option<Vec3>, linear: bool)
+{
+    if SAMPLES > 1
+    {
+        let samples = framebuffer.samples.as_mut().expect("multisampling without a sample buffer");
+
+        for s_i in (0..SAMPLES).filter(|s_i| mask & (1 << s_i) != 0)
+        {
+            samples[p_i * SAMPLES + s_i] = color.unwrap_or(Vec3::ZERO).extend(1.0);
+        }
+
+        return;
+    }
+
+    match color
+    {
+        Some(rgb) if linear =>
+        {
+            if let Some(hdr) = &mut framebuffer.hdr
+            {
+                hdr[p_i] = rgb.extend(1.0);
+            }
+            else
+            {
+                framebuffer.color[p_i] = from_linear_rgb(rgb);
             }
         }
+
+        Some(rgb) => framebuffer.color[p_i] = from_vec3_rgb(rgb),
+
+        None => framebuffer.color[p_i] = 0,
     }
 }
 
-// courtesy of Luca
+// vertices are expected to be pre-divided by w, returns linear rgb
+fn shade_fragment(vertices: &[Vertex; 3], barycentric: Vec3, correction: f32, texture: &Option<Texture>) -> Vec3
+{
+    let [v0, v1, v2] = vertices;
+    let (a, b, c) = (barycentric.x, barycentric.y, barycentric.z);
+
+    if let Some(texture) = texture
+    {
+        let mut uv = v0.uv * a;
+        uv += v1.uv * b;
+        uv += v2.uv * c;
+        uv *= correction;
+
+        return texture.sample(uv).truncate();
+    }
+    else 
+    {
+        let mut rgb = v0.color * a;
+        rgb += v1.color * b;
+        rgb += v2.color * c;
+        rgb *= correction;
+
+        return rgb;
+    }
+}
+
+pub struct GltfModel
+{
+    pub mesh: Mesh,
+    // node the mesh was found on, animation channels targeting it move the whole mesh
+    pub node: Option<usize>,
+    // the node's local transform, identity for skinned meshes
+    pub transform: Transform,
+    pub animations: Vec<AnimationClip>,
+}
+
 pub fn load_gltf(path: &Path) -> Mesh 
+{
+    return load_gltf_model(path).mesh;
+}
+
+pub fn load_gltf_model(path: &Path) -> GltfModel 
+{
+    return try_load_gltf_model(path).unwrap_or_else(|error| panic!("{}", error));
+}
+
+// courtesy of Luca
+pub fn try_load_gltf_model(path: &Path) -> Result<GltfModel, String>
 {
     // handle loading textures, cameras, meshes here
-    let (document, buffers, _images) = gltf::import(path).unwrap();
+    let (document, buffers, _images) = gltf::import(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let animations: Vec<AnimationClip> = document.animations().map(|a| AnimationClip::load_from_gltf(&a, &buffers)).collect();
 
     for scene in document.scenes() 
     {
@@ -229,10 +461,35 @@ pub fn load_gltf(path: &Path) -> Mesh
             );
             if let Some(mesh) = node.mesh() 
             {
-                return Mesh::load_from_gltf(&mesh, &buffers);
+                let mut result = Mesh::load_from_gltf(&mesh, &buffers).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+                if let Some(skin) = node.skin()
+                {
+                    let skin = Skin::load_from_gltf(&document, &skin, &buffers);
+
+                    // skin_vertex indexes the joint matrices with every weighted joint
+                    let bad_joint = result.vertices.iter().flat_map(|v| (0..4).filter(|i| v.weights[*i] > 0.0).map(|i| v.joints[i] as usize)).find(|joint| *joint >= skin.joints.len());
+                    if let Some(joint) = bad_joint
+                    {
+                        return Err(format!("{}: vertex uses joint {} but the skin has {} joints", path.display(), joint, skin.joints.len()));
+                    }
+
+                    result.skin = Some(skin);
+                }
+
+                let (translation, rotation, scale) = node.transform().decomposed();
+
+                // the joint matrices already place a skinned mesh, so the spec has its node's transform ignored
+                let transform = match result.skin
+                {
+                    Some(_) => Transform::IDENTITY,
+                    None => Transform { translation: Vec3::from(translation), rotation: Quat::from_array(rotation), scale: Vec3::from(scale) },
+                };
+
+                return Ok(GltfModel { mesh: result, node: Some(node.index()), transform, animations });
             }
         }
     }
 
-    Mesh::new()
-}
\ No newline at end of file
+    return Ok(GltfModel { mesh: Mesh::new(), node: None, transform: Transform::IDENTITY, animations });
+}
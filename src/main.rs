@@ -1,51 +1,221 @@
-use glam::{Vec2, Vec3, UVec3, Vec4, Mat4, Quat};
-use minifb::{Key, Window, WindowOptions};
-use std::path::Path;
+use glam::{Quat, Vec2, Vec3, UVec3, Vec4};
+use minifb::{Key, MouseButton, Window, WindowOptions};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-mod transform;
-use transform::Transform;
+mod hud;
+use hud::Hud;
+
+mod config;
+use config::ViewerConfig;
 
 use rusterizer::*;
 
-const WIDTH:  usize = 1920;
-const HEIGHT: usize = 1080;
+// seconds for one full turn of the meshes that don't ship their own animation
+const TURNTABLE_PERIOD: f32 = 20.0;
 
-const UPSCALE: usize = 1;
+// optional, used when no --config is given
+const VIEWER_CONFIG_PATH: &str = "viewer.toml";
 
-fn main() 
+// simulation step while recording or replaying, so a replay advances exactly like the recording did
+const FIXED_TIMESTEP: f32 = 1.0 / 60.0;
+
+const USAGE: &str = "usage: rusterizer [--config <file>] [--scene <model>] [--size <width>x<height>] [--upscale <factor>]
+                  [--record <file>] [--replay <file>] [--headless] [--frames <dir>] [--stats <file>]";
+
+const WINDOW_TITLE: &str = "Rusterizer - H: HUD, 1-6: debug views, T/+/-: tonemap, M: MSAA, F: FXAA/SMAA, O: occlusion culling, G/R/S: gizmo, ESC: exit";
+
+#[derive(Default)]
+struct Options
 {
-    let mut buffer: Vec<u32> = vec![0; WIDTH * HEIGHT];
-    let mut z_buffer: Vec<f32> = vec![f32::INFINITY; WIDTH * HEIGHT];
+    // viewer.toml style file, replaces the defaults
+    config: Option<PathBuf>,
+    // single model to show instead of the configured ones
+    scene: Option<PathBuf>,
+    size: Option<(usize, usize)>,
+    upscale: Option<f32>,
+    // input of the session is written here on exit
+    record: Option<PathBuf>,
+    // input comes from this file instead of the window
+    replay: Option<PathBuf>,
+    // no window, needs a replay to drive it
+    headless: bool,
+    // every presented frame is saved here as a ppm
+    frames: Option<PathBuf>,
+    // render stats of every frame are written here as json on exit
+    stats: Option<PathBuf>,
+}
 
-    let mut upscale_buffer: Vec<u32> = vec![0; WIDTH*UPSCALE * HEIGHT*UPSCALE];
+fn parse_options() -> Result<Options, String>
+{
+    let mut options = Options::default();
+    let mut args = std::env::args().skip(1);
 
-    let mut window = Window::new(
-        "Test - ESC to exit",
-        WIDTH*UPSCALE,
-        HEIGHT*UPSCALE,
-        WindowOptions::default(),
-    )
-    .unwrap_or_else(|e| 
+    while let Some(arg) = args.next()
     {
-        panic!("{}", e);
+        let mut value = || args.next().map(PathBuf::from).ok_or(format!("{} needs a value", arg));
+
+        match arg.as_str()
+        {
+            "--config" => options.config = Some(value()?),
+            "--scene" => options.scene = Some(value()?),
+            "--size" =>
+            {
+                let size = value()?;
+                let size = size.to_str().and_then(|size| size.split_once('x')).and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+                options.size = Some(size.ok_or(format!("bad value for {}", arg))?);
+            }
+            "--upscale" =>
+            {
+                let upscale = value()?;
+                options.upscale = Some(upscale.to_str().and_then(|upscale| upscale.parse().ok()).ok_or(format!("bad value for {}", arg))?);
+            }
+            "--record" => options.record = Some(value()?),
+            "--replay" => options.replay = Some(value()?),
+            "--frames" => options.frames = Some(value()?),
+            "--stats" => options.stats = Some(value()?),
+            "--headless" => options.headless = true,
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+
+    if options.headless && options.replay.is_none()
+    {
+        return Err("--headless needs --replay".to_string());
+    }
+
+    return Ok(options);
+}
+
+// defaults, then the config file, then whatever the command line overrides
+fn load_config(options: &Options) -> Result<ViewerConfig, String>
+{
+    let mut config = match &options.config
+    {
+        Some(path) => ViewerConfig::load(path)?,
+        None if Path::new(VIEWER_CONFIG_PATH).exists() => ViewerConfig::load(Path::new(VIEWER_CONFIG_PATH))?,
+        None => ViewerConfig::default(),
+    };
+
+    if let Some(scene) = &options.scene
+    {
+        config.models = vec![config::ModelConfig { path: scene.clone(), texture: None, translation: [0.0, 0.0, -15.0], scale: 1.0 }];
+    }
+
+    if let Some((width, height)) = options.size
+    {
+        config.width = width;
+        config.height = height;
+    }
+
+    if let Some(upscale) = options.upscale
+    {
+        config.upscale = upscale;
+    }
+
+    config.validate()?;
+
+    return Ok(config);
+}
+
+// gltf or obj, the gltf node keeps its transform and animations
+fn load_model(model: &config::ModelConfig) -> Result<GltfModel, String>
+{
+    let extension = model.path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+
+    let mut loaded = match extension.as_deref()
+    {
+        Some("obj") => GltfModel { mesh: load_obj(&model.path)?, node: None, transform: Transform::IDENTITY, animations: Vec::new() },
+        _ => try_load_gltf_model(&model.path)?,
+    };
+
+    if let Some(texture) = &model.texture
+    {
+        loaded.mesh.texture = Some(Texture::try_load(texture)?);
+    }
+
+    return Ok(loaded);
+}
+
+fn main() 
+{
+    let options = parse_options().unwrap_or_else(|error|
+    {
+        eprintln!("{}\n{}", error, USAGE);
+        std::process::exit(2);
     });
 
-    // Limit to max ~60 fps update rate
-    window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
+    let config = load_config(&options).unwrap_or_else(|error|
+    {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    });
 
-    let test_albedo = Texture::load(Path::new("assets/test.jpg"));
-    let helmet_albedo = Texture::load(Path::new("assets/helmet_albedo.jpg"));
+    let (mut width, mut height) = (config.width, config.height);
+    let (mut window_width, mut window_height) = config.window_size();
 
-    let mut meshes = 
-    [
-        Mesh
+    let replay = options.replay.as_ref().map(|path| InputRecording::load(path).unwrap_or_else(|error|
+    {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }));
+    let mut recording = options.record.as_ref().map(|_| InputRecording::new(FIXED_TIMESTEP));
+    let fixed_timestep = replay.as_ref().or(recording.as_ref()).map(|recording| recording.timestep);
+
+    // frame time driven, so it stays off whenever the session has to be reproducible
+    let mut dynamic_resolution = config.target_frame_ms
+        .filter(|_| fixed_timestep.is_none())
+        .map(|ms| DynamicResolution::new(ms / 1000.0, config.min_resolution_scale));
+    let mut resolution_changed = false;
+
+    if let Some(frames) = &options.frames
+    {
+        if let Err(error) = std::fs::create_dir_all(frames)
+        {
+            eprintln!("{}: {}", frames.display(), error);
+            std::process::exit(1);
+        }
+    }
+
+    // one json object per frame
+    let mut stats_log: Vec<String> = Vec::new();
+
+    let mut framebuffer = Framebuffer::with_hdr(width, height);
+    framebuffer.enable_ids();
+    framebuffer.set_antialiasing(config.antialiasing());
+    let mut renderer = Renderer::new(framebuffer);
+    renderer.post_process = PostChain::with_effects(&config.post_process());
+
+    // what the window shows when it isn't the same size as the framebuffer
+    let mut upscale_buffer: Vec<u32> = vec![0; window_width * window_height];
+
+    let mut window = if options.headless { None } else { Some(create_window(window_width, window_height)) };
+
+    let environment = config.environment.as_deref().map(|path| Environment::try_load_equirectangular(path).unwrap_or_else(|error|
+    {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }));
+
+    let mut meshes = Vec::new();
+    let mut transforms = Vec::new();
+    // animated local transform of each mesh's node, placed by `transforms`
+    let mut node_transforms = Vec::new();
+    let mut nodes = Vec::new();
+    let mut clips = Vec::new();
+
+    if config.test_quad
+    {
+        let test_albedo = Texture::load(Path::new("assets/test.jpg"));
+
+        let mut quad = Mesh
         { 
             vertices: 
             vec![
-                Vertex{ pos: Vec4::new(-1.0, -1.0, 0.0, 1.0), normal: Vec3::new(0.0, 0.0, 1.0), uv: Vec2::new(0.0, 0.0), color: Vec3::new(1.0, 1.0, 1.0) },
-                Vertex{ pos: Vec4::new( 1.0, -1.0, 0.0, 1.0), normal: Vec3::new(0.0, 0.0, 1.0), uv: Vec2::new(1.0, 0.0), color: Vec3::new(1.0, 1.0, 1.0) },
-                Vertex{ pos: Vec4::new(-1.0,  1.0, 0.0, 1.0), normal: Vec3::new(0.0, 0.0, 1.0), uv: Vec2::new(0.0, 1.0), color: Vec3::new(0.0, 0.0, 0.0) },
-                Vertex{ pos: Vec4::new( 1.0,  1.0, 0.0, 1.0), normal: Vec3::new(0.0, 0.0, 1.0), uv: Vec2::new(1.0, 1.0), color: Vec3::new(1.0, 1.0, 1.0) }
+                Vertex::new(Vec4::new(-1.0, -1.0, 0.0, 1.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 1.0, 1.0), Vec2::new(0.0, 0.0)),
+                Vertex::new(Vec4::new( 1.0, -1.0, 0.0, 1.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 1.0, 1.0), Vec2::new(1.0, 0.0)),
+                Vertex::new(Vec4::new(-1.0,  1.0, 0.0, 1.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, 0.0), Vec2::new(0.0, 1.0)),
+                Vertex::new(Vec4::new( 1.0,  1.0, 0.0, 1.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 1.0, 1.0), Vec2::new(1.0, 1.0))
             ],
 
             indices:
@@ -54,107 +224,482 @@ fn main()
                 UVec3 { x: 1, y: 2, z: 3}
             ],
 
-            texture: Some(test_albedo)
-        },
-        load_gltf(Path::new("assets/DamagedHelmet.gltf"))
-    ];
+            texture: Some(test_albedo),
 
-    meshes[1].texture = Some(helmet_albedo);
+            ..Mesh::new()
+        };
+        quad.compute_bounds();
 
-    let mut transforms = vec![Transform::IDENTITY; meshes.len()];
+        let mut transform = Transform::from_translation(Vec3::new(-8.0, 0.0, -11.0));
+        transform.scale *= 3.0;
 
-    transforms[0].translation.z -= 11.0;
-    transforms[0].translation.x -= 8.0;
-    transforms[0].scale *= 3.0;
-    let mut rot0 = 0.0;
-    
-    transforms[1].translation.z -= 15.0;
-    //transforms[1].translation.x -= 3.0;
-    transforms[1].scale *= 2.0;
-    let mut rot1 = 0.0;
+        meshes.push(quad);
+        transforms.push(transform);
+        node_transforms.push(Transform::IDENTITY);
+        // the quad is not part of any gltf file, its clip uses node 0 for itself
+        nodes.push(Some(0));
+        clips.push(AnimationClip::turntable(0, Vec3::X, TURNTABLE_PERIOD, Quat::IDENTITY));
+    }
 
-    let mut eye = Vec3::new(0.0, 0.0, 0.0);
+    for model_config in &config.models
+    {
+        let model = load_model(model_config).unwrap_or_else(|error|
+        {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        });
+
+        // meshes without a node of their own (obj) animate as node 0
+        let node = model.node.unwrap_or(0);
+
+        let mut transform = Transform::from_translation(Vec3::from(model_config.translation));
+        transform.scale *= model_config.scale;
+
+        meshes.push(model.mesh);
+        transforms.push(transform);
+        node_transforms.push(model.transform);
+        nodes.push(Some(node));
+        clips.push(model.animations.into_iter().next().unwrap_or_else(|| AnimationClip::turntable(node, Vec3::NEG_Y, TURNTABLE_PERIOD, model.transform.rotation)));
+    }
+
+    let mut players: Vec<AnimationPlayer> = clips.iter().map(|_| AnimationPlayer::new()).collect();
+    let mut last_frame = Instant::now();
+
+    let mut camera = Camera::new(std::f32::consts::PI / 4.0, width as f32 / height as f32, 5.0, 100.0);
 
-    let perspective = Mat4::perspective_rh(std::f32::consts::PI / 4.0, WIDTH as f32 / HEIGHT as f32, 5.0, 100.0);
+    let mut hud = Hud::new();
+    hud.show_timings = replay.is_none();
 
-    while window.is_open() && !window.is_key_down(Key::Escape)
+    let mut input_map = default_input_map();
+    for (action, bindings) in config.bindings()
     {
-        buffer.fill(0);
-        z_buffer.fill(f32::INFINITY);
+        input_map.rebind(&action, bindings);
+    }
+    let mut input = Input::new(input_map);
 
-        update_camera_eye(&mut eye, &window);
+    let mut selection = String::new();
+    let mut selected: Option<usize> = None;
+    let mut gizmo = Gizmo::new();
 
-        transforms[0].rotation = Quat::from_euler(glam::EulerRot::XYZ, rot0, 0.0, 0.0);
-        rot0 += 0.005;
+    for frame_index in 0..
+    {
+        if window.as_ref().is_some_and(|window| !window.is_open())
+        {
+            break;
+        }
 
-        transforms[1].rotation = Quat::from_euler(glam::EulerRot::XYZ, 0.0, rot1, 0.0);
-        rot1 -= 0.005;
+        // follow the window size and the dynamic resolution, the upscale step stretches whatever we render to fit
+        if let Some(window) = &window
+        {
+            let window_size = window.get_size();
+            let window_size = (window_size.0.max(1), window_size.1.max(1));
 
-        let view = Mat4::look_at_rh(eye, eye + Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0));
+            if window_size != (window_width, window_height) || resolution_changed
+            {
+                (window_width, window_height) = window_size;
+                upscale_buffer.resize(window_width * window_height, 0);
+
+                // a replay renders at the sizes it was recorded with instead, see below
+                if replay.is_none()
+                {
+                    (width, height) = config.render_size(window_size, dynamic_resolution.as_ref().map_or(1.0, |d| d.scale()));
+                    renderer.resize(width, height);
+                    camera.aspect = width as f32 / height as f32;
+                }
+                resolution_changed = false;
+            }
+        }
+
+        let frame = match (&replay, &window)
+        {
+            (Some(replay), _) => match replay.frames.get(frame_index)
+            {
+                Some(frame) => frame.clone(),
+                None => break,
+            },
+            (None, Some(window)) => InputFrame::capture(window, (width, height)),
+            (None, None) => break,
+        };
+
+        // recorded mouse positions are in render pixels and the camera aspect follows the render size,
+        // so the replay has to render each frame at the size it had in the session
+        if let Some(&size) = replay.as_ref().and_then(|replay| replay.render_sizes.get(frame_index))
+        {
+            if size != (width, height)
+            {
+                (width, height) = size;
+                renderer.resize(width, height);
+                camera.aspect = width as f32 / height as f32;
+            }
+        }
+
+        if let Some(recording) = &mut recording
+        {
+            recording.push(&frame, (width, height));
+        }
+
+        let now = Instant::now();
+        let frame_time = (now - last_frame).as_secs_f32();
+        last_frame = now;
+
+        let dt = fixed_timestep.unwrap_or(frame_time);
+
+        input.update(frame, dt);
+        if input.is_pressed("quit")
+        {
+            break;
+        }
+
+        if update_debug_mode(&mut renderer.debug.mode, &input) | update_tonemap(&mut renderer.tonemap, &input) | update_antialiasing(&mut renderer.framebuffer, &input) | update_post_process(&mut renderer.post_process, &input)
+        {
+            if let Some(window) = &mut window
+            {
+                window.set_title(&format!("{} - {} - {} {:+.1} EV - {} {}", WINDOW_TITLE, renderer.debug.mode.name(), renderer.tonemap.operator.name(), renderer.tonemap.exposure, renderer.framebuffer.antialiasing().name(), renderer.post_process.name()));
+            }
+        }
+
+        renderer.begin_frame();
+        let render_start = Instant::now();
+
+        // a replayed hud shows the simulated time so its frames stay reproducible
+        hud.tick(if replay.is_some() { dt } else { frame_time });
+        if input.is_pressed("toggle_hud")
+        {
+            hud.visible = !hud.visible;
+        }
+
+        if input.is_pressed("toggle_occlusion_culling")
+        {
+            renderer.state.occlusion_culling = !renderer.state.occlusion_culling;
+        }
+
+        update_camera_eye(&mut camera.position, &input);
+        update_gizmo_mode(&mut gizmo, &input);
+
+        // the gizmo gets the mouse first, picking only happens when it isn't grabbed
+        let mut mouse = input.mouse_state("select", false);
+        if let Some(m_i) = selected
+        {
+            gizmo.update(&mut transforms[m_i], &camera, (width, height), &mut mouse);
+        }
+
+        for (m_i, clip) in clips.iter().enumerate()
+        {
+            players[m_i].advance(dt, clip);
+
+            let mesh = &mut meshes[m_i];
+            let node_transform = &mut node_transforms[m_i];
+
+            clip.sample(players[m_i].time, |node, property|
+            {
+                if let Property::MorphWeights(weights) = &property
+                {
+                    if Some(node) == nodes[m_i]
+                    {
+                        mesh.set_morph_weights(weights);
+                    }
+                }
+                else if Some(node) == nodes[m_i]
+                {
+                    node_transform.set_property(&property);
+                }
+                else if let Some(skin) = &mut mesh.skin
+                {
+                    skin.set_joint_property(node, &property);
+                }
+            });
+
+            if let Some(skin) = &mut mesh.skin
+            {
+                skin.update();
+            }
+        }
+
+        let models: Vec<Transform> = transforms.iter().zip(node_transforms.iter()).map(|(t, n)| t.mul_transform(n)).collect();
+
+        // front to back so nearer meshes can occlude the ones behind them
+        let mut draw_order: Vec<usize> = (0..meshes.len()).collect();
+        draw_order.sort_by(|a, b|
+        {
+            let distance = |m_i: usize| models[m_i].to_matrix().transform_point3(meshes[m_i].bounding_sphere.center).distance(camera.position);
+            distance(*a).total_cmp(&distance(*b))
+        });
 
         // render meshes
-        for (m_i, mesh) in meshes.iter().enumerate()
+        for m_i in draw_order
         {
-            let model = Mat4::from_translation(transforms[m_i].translation) * Mat4::from_quat(transforms[m_i].rotation) * Mat4::from_scale(transforms[m_i].scale);
-            let mvp = perspective * view * model;
+            renderer.state.object_id = m_i as u32;
+            renderer.draw_mesh(&meshes[m_i], &models[m_i], &camera);
 
-            for (_t_i, vertex_indices) in mesh.indices.iter().enumerate()
+            // only a handful of meshes, so the pyramid can be refreshed after each one
+            if renderer.state.occlusion_culling
             {
-                clip_and_rasterize_triangle([mesh.vertices[vertex_indices.x as usize], mesh.vertices[vertex_indices.y as usize], mesh.vertices[vertex_indices.z as usize]], mvp, 
-                                    &mesh.texture, &mut buffer, &mut z_buffer, (WIDTH, HEIGHT));
+                renderer.build_depth_pyramid();
             }
         }
 
-        // upscale resolution
-        for i in 0..(WIDTH*UPSCALE*HEIGHT*UPSCALE)
+        if let Some(environment) = &environment
         {
-            let x_up = i % (WIDTH*UPSCALE);
-            let y_up = i / (WIDTH*UPSCALE);
+            renderer.draw_background(environment, &camera);
+        }
+
+        renderer.end_frame();
+
+        if options.stats.is_some()
+        {
+            stats_log.push(renderer.stats.to_json());
+        }
+
+        if let Some(dynamic_resolution) = &mut dynamic_resolution
+        {
+            resolution_changed = dynamic_resolution.update(render_start.elapsed().as_secs_f32());
+        }
+
+        // click to pick, the id buffer and the cpu ray cast should agree
+        if input.is_pressed("select") && !gizmo.is_active()
+        {
+            let posed: Vec<(&Mesh, &Transform)> = meshes.iter().zip(models.iter()).collect();
+            let ray = camera.screen_ray(mouse.pos, (width, height));
 
-            let x = x_up / UPSCALE;
-            let y = y_up / UPSCALE;
+            let hit = pick(&ray, &posed);
+            selected = hit.map(|hit| hit.mesh);
 
-            let buffer_index = x + y * WIDTH;
+            selection = match hit
+            {
+                Some(hit) => format!("Ray mesh {} tri {} at {:.2} {:.2} {:.2}", hit.mesh, hit.triangle, hit.point.x, hit.point.y, hit.point.z),
+                None => "Ray miss".to_string(),
+            };
 
-            upscale_buffer[i] = buffer[buffer_index];
+            selection += &match renderer.framebuffer.id_at(mouse.pos.x as usize, mouse.pos.y as usize)
+            {
+                Some(id) => format!("\nId mesh {} tri {}", id.object, id.triangle),
+                None => "\nId miss".to_string(),
+            };
+        }
+
+        if let Some(m_i) = selected
+        {
+            gizmo.draw(&mut renderer.framebuffer.color, (width, height), &transforms[m_i], &camera);
+        }
+
+        let status = format!("Render {}x{} in {}x{} {} {}\n{}", width, height, window_width, window_height, renderer.framebuffer.antialiasing().name(), renderer.post_process.name(), selection);
+        hud.draw(&mut renderer.framebuffer.color, (width, height), &renderer.stats, camera.position, &status);
+
+        if let Some(frames) = &options.frames
+        {
+            let path = frames.join(format!("frame_{:05}.ppm", frame_index));
+            if let Err(error) = renderer.framebuffer.save_ppm(&path)
+            {
+                eprintln!("{}: {}", path.display(), error);
+            }
         }
 
         // We unwrap here as we want this code to exit if it fails. Real applications may want to handle this in a different way
-        window
-            .update_with_buffer(&buffer, WIDTH, HEIGHT)
-            .unwrap();
+        if let Some(window) = &mut window
+        {
+            if (window_width, window_height) == (width, height)
+            {
+                window.update_with_buffer(&renderer.framebuffer.color, width, height).unwrap();
+            }
+            else
+            {
+                renderer.framebuffer.resample_into(&mut upscale_buffer, (window_width, window_height));
+                window.update_with_buffer(&upscale_buffer, window_width, window_height).unwrap();
+            }
+        }
+    }
+
+    if let Some(path) = &options.stats
+    {
+        if let Err(error) = std::fs::write(path, format!("[\n{}\n]\n", stats_log.join(",\n")))
+        {
+            eprintln!("{}: {}", path.display(), error);
+        }
+    }
+
+    if let (Some(recording), Some(path)) = (&recording, &options.record)
+    {
+        if let Err(error) = recording.save(path)
+        {
+            eprintln!("{}: {}", path.display(), error);
+        }
+    }
+}
+
+fn create_window(width: usize, height: usize) -> Window
+{
+    let mut window = Window::new(
+        WINDOW_TITLE,
+        width,
+        height,
+        WindowOptions { resize: true, ..WindowOptions::default() },
+    )
+    .unwrap_or_else(|e| 
+    {
+        panic!("{}", e);
+    });
+
+    // Limit to max ~60 fps update rate
+    window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
+
+    return window;
+}
+
+fn default_input_map() -> InputMap
+{
+    let mut map = InputMap::new();
+
+    let keys =
+    [
+        ("quit", Key::Escape),
+        ("move_left", Key::Left),
+        ("move_right", Key::Right),
+        ("move_up", Key::Space),
+        ("move_down", Key::LeftShift),
+        ("move_forward", Key::Up),
+        ("move_back", Key::Down),
+        ("toggle_hud", Key::H),
+        ("toggle_occlusion_culling", Key::O),
+        ("next_tonemap", Key::T),
+        ("next_antialiasing", Key::M),
+        ("next_post_process", Key::F),
+        ("exposure_up", Key::Equal),
+        ("exposure_up", Key::NumPadPlus),
+        ("exposure_down", Key::Minus),
+        ("exposure_down", Key::NumPadMinus),
+        ("gizmo_translate", Key::G),
+        ("gizmo_rotate", Key::R),
+        ("gizmo_scale", Key::S),
+        ("debug_view_1", Key::Key1),
+        ("debug_view_2", Key::Key2),
+        ("debug_view_3", Key::Key3),
+        ("debug_view_4", Key::Key4),
+        ("debug_view_5", Key::Key5),
+        ("debug_view_6", Key::Key6),
+    ];
+
+    for (action, key) in keys
+    {
+        map.bind(action, Binding::Key(key));
     }
+
+    map.bind("select", Binding::Mouse(MouseButton::Left));
+    map.bind("pan", Binding::Mouse(MouseButton::Right));
+
+    return map;
 }
 
-fn update_camera_eye(eye: &mut Vec3, window: &Window)
+fn update_camera_eye(eye: &mut Vec3, input: &Input)
 {
-    if window.is_key_down(Key::Left)
+    let moves =
+    [
+        ("move_left", Vec3::NEG_X),
+        ("move_right", Vec3::X),
+        ("move_up", Vec3::Y),
+        ("move_down", Vec3::NEG_Y),
+        ("move_forward", Vec3::NEG_Z),
+        ("move_back", Vec3::Z),
+    ];
+
+    for (action, direction) in moves
     {
-        eye.x -= 0.5;
+        if input.is_down(action)
+        {
+            *eye += direction * 0.5;
+        }
     }
 
-    if window.is_key_down(Key::Right)
+    // wheel dollies, dragging with the pan action slides the eye with the mouse
+    eye.z -= input.wheel() * 0.5;
+
+    if input.is_down("pan")
     {
-        eye.x += 0.5;
+        let delta = input.mouse_delta();
+        eye.x -= delta.x * 0.02;
+        eye.y += delta.y * 0.02;
     }
+}
 
-    if window.is_key_down(Key::Space)
+fn update_gizmo_mode(gizmo: &mut Gizmo, input: &Input)
+{
+    let modes = [("gizmo_translate", GizmoMode::Translate), ("gizmo_rotate", GizmoMode::Rotate), ("gizmo_scale", GizmoMode::Scale)];
+
+    for (action, mode) in modes
     {
-        eye.y += 0.5;
+        if input.is_pressed(action)
+        {
+            gizmo.set_mode(mode);
+        }
+    }
+}
+
+// debug_view_1 to debug_view_6 select a debug view, returns true if the mode changed
+fn update_debug_mode(mode: &mut DebugMode, input: &Input) -> bool
+{
+    for (m_i, new_mode) in DebugMode::ALL.iter().enumerate()
+    {
+        if input.is_pressed(&format!("debug_view_{}", m_i + 1)) && *mode != *new_mode
+        {
+            *mode = *new_mode;
+            return true;
+        }
+    }
+
+    return false;
+}
+
+// cycles the tonemap operator and changes exposure by half a stop, repeating while held
+fn update_tonemap(tonemap: &mut Tonemap, input: &Input) -> bool
+{
+    let mut changed = false;
+
+    if input.is_pressed("next_tonemap")
+    {
+        tonemap.operator = tonemap.operator.next();
+        changed = true;
+    }
+
+    if input.is_pressed_repeat("exposure_up")
+    {
+        tonemap.exposure += 0.5;
+        changed = true;
     }
 
-    if window.is_key_down(Key::LeftShift)
+    if input.is_pressed_repeat("exposure_down")
     {
-        eye.y -= 0.5;
+        tonemap.exposure -= 0.5;
+        changed = true;
     }
 
-    if window.is_key_down(Key::Up)
+    return changed;
+}
+
+// cycles none and the multisampled modes, returns true if the mode changed
+fn update_antialiasing(framebuffer: &mut Framebuffer, input: &Input) -> bool
+{
+    if input.is_pressed("next_antialiasing")
     {
-        eye.z -= 0.5;
+        framebuffer.set_antialiasing(framebuffer.antialiasing().next());
+        return true;
     }
 
-    if window.is_key_down(Key::Down)
+    return false;
+}
+
+// cycles between no post-processing, fxaa and smaa, returns true if the chain changed
+fn update_post_process(post_process: &mut PostChain, input: &Input) -> bool
+{
+    if !input.is_pressed("next_post_process")
     {
-        eye.z += 0.5;
+        return false;
     }
-}
\ No newline at end of file
+
+    post_process.effects = match post_process.effects.as_slice()
+    {
+        [] => vec![PostEffect::Fxaa],
+        [PostEffect::Fxaa] => vec![PostEffect::Smaa],
+        _ => Vec::new(),
+    };
+
+    return true;
+}
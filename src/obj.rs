@@ -0,0 +1,134 @@
+use glam::{UVec3, Vec2, Vec3};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::geometry::{Mesh, fill_missing_normals};
+
+// wavefront obj, positions, uvs and normals only; polygons are fanned into triangles
+// and faces without normals get smooth ones averaged from the faces around them
+pub fn load_obj(path: &Path) -> Result<Mesh, String>
+{
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let error = |l_i: usize, message: String| format!("{}:{}: {}", path.display(), l_i + 1, message);
+
+    let mut obj_positions: Vec<Vec3> = Vec::new();
+    let mut obj_uvs: Vec<Vec2> = Vec::new();
+    let mut obj_normals: Vec<Vec3> = Vec::new();
+
+    // one vertex per distinct position/uv/normal combination
+    let mut vertex_lookup: HashMap<(usize, Option<usize>, Option<usize>), u32> = HashMap::new();
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<Vec2> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut has_normal: Vec<bool> = Vec::new();
+    let mut triangles: Vec<UVec3> = Vec::new();
+
+    for (l_i, line) in text.lines().enumerate()
+    {
+        let line = line.split('#').next().unwrap_or("");
+        let mut fields = line.split_whitespace();
+
+        let Some(keyword) = fields.next() else { continue };
+        let fields: Vec<&str> = fields.collect();
+
+        let numbers = |count: usize| -> Result<Vec<f32>, String>
+        {
+            if fields.len() < count
+            {
+                return Err(error(l_i, format!("'{}' needs {} values", keyword, count)));
+            }
+
+            return fields[..count].iter().map(|field| field.parse::<f32>().map_err(|_| error(l_i, format!("bad number '{}'", field)))).collect();
+        };
+
+        match keyword
+        {
+            "v" =>
+            {
+                let v = numbers(3)?;
+                obj_positions.push(Vec3::new(v[0], v[1], v[2]));
+            }
+
+            "vt" =>
+            {
+                let v = numbers(2)?;
+                // obj puts v = 0 at the bottom of the image, gltf and our sampler at the top
+                obj_uvs.push(Vec2::new(v[0], 1.0 - v[1]));
+            }
+
+            "vn" =>
+            {
+                let v = numbers(3)?;
+                obj_normals.push(Vec3::new(v[0], v[1], v[2]).normalize_or_zero());
+            }
+
+            "f" =>
+            {
+                if fields.len() < 3
+                {
+                    return Err(error(l_i, "face needs at least 3 vertices".to_string()));
+                }
+
+                let mut face = Vec::with_capacity(fields.len());
+
+                for field in &fields
+                {
+                    let mut parts = field.split('/');
+
+                    let position = resolve_index(parts.next(), obj_positions.len()).map_err(|e| error(l_i, e))?
+                        .ok_or(error(l_i, format!("face vertex '{}' has no position", field)))?;
+                    let uv = resolve_index(parts.next(), obj_uvs.len()).map_err(|e| error(l_i, e))?;
+                    let normal = resolve_index(parts.next(), obj_normals.len()).map_err(|e| error(l_i, e))?;
+
+                    let index = *vertex_lookup.entry((position, uv, normal)).or_insert_with(||
+                    {
+                        positions.push(obj_positions[position]);
+                        uvs.push(uv.map_or(Vec2::ZERO, |uv| obj_uvs[uv]));
+                        normals.push(normal.map_or(Vec3::ZERO, |normal| obj_normals[normal]));
+                        has_normal.push(normal.is_some());
+                        (positions.len() - 1) as u32
+                    });
+
+                    face.push(index);
+                }
+
+                for f_i in 1..face.len() - 1
+                {
+                    triangles.push(UVec3::new(face[0], face[f_i], face[f_i + 1]));
+                }
+            }
+
+            // groups, materials and smoothing groups don't change the geometry
+            _ => {}
+        }
+    }
+
+    if triangles.is_empty()
+    {
+        return Err(format!("{}: no faces", path.display()));
+    }
+
+    // the vertices the file left without a normal get a smooth one
+    fill_missing_normals(&mut normals, &has_normal, &positions, &triangles);
+
+    let mut mesh = Mesh::new();
+    mesh.add_section_from_buffers(&triangles, &positions, &normals, &[], &uvs);
+
+    return Ok(mesh);
+}
+
+// obj indices start at 1, negative ones count back from the latest element
+fn resolve_index(field: Option<&str>, count: usize) -> Result<Option<usize>, String>
+{
+    let Some(field) = field.filter(|field| !field.is_empty()) else { return Ok(None) };
+
+    let index: i64 = field.parse().map_err(|_| format!("bad index '{}'", field))?;
+    let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+
+    if resolved < 0 || resolved >= count as i64
+    {
+        return Err(format!("index {} out of range", index));
+    }
+
+    return Ok(Some(resolved as usize));
+}
@@ -0,0 +1,158 @@
+use glam::{Vec2, Vec3, Vec4Swizzles};
+
+use crate::bounds::Aabb;
+use crate::camera::Camera;
+use crate::geometry::Mesh;
+use crate::transform::Transform;
+
+// what the id buffer stores per pixel
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PickId
+{
+    pub object: u32,
+    pub triangle: u32,
+}
+
+impl PickId
+{
+    pub const NONE: Self = Self { object: u32::MAX, triangle: u32::MAX };
+
+    pub fn is_none(&self) -> bool
+    {
+        return *self == Self::NONE;
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ray
+{
+    pub origin: Vec3,
+    // not necessarily normalized, hit distances are in multiples of it
+    pub direction: Vec3,
+}
+
+impl Ray
+{
+    pub fn at(&self, t: f32) -> Vec3
+    {
+        return self.origin + self.direction * t;
+    }
+
+    // slab test, returns the entry distance
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32>
+    {
+        let inverse = self.direction.recip();
+        let t0 = (aabb.min - self.origin) * inverse;
+        let t1 = (aabb.max - self.origin) * inverse;
+
+        let near = t0.min(t1).max_element().max(0.0);
+        let far = t0.max(t1).min_element();
+
+        return if near <= far { Some(near) } else { None };
+    }
+
+    // Möller-Trumbore, returns the distance and the barycentrics of the hit
+    pub fn intersect_triangle(&self, v0: Vec3, v1: Vec3, v2: Vec3) -> Option<(f32, Vec3)>
+    {
+        let edge1 = v1 - v0;
+        let edge2 = v2 - v0;
+        let p = self.direction.cross(edge2);
+        let det = edge1.dot(p);
+
+        if det.abs() < f32::EPSILON * f32::EPSILON
+        {
+            return None;
+        }
+
+        let inverse_det = 1.0 / det;
+        let s = self.origin - v0;
+        let u = s.dot(p) * inverse_det;
+        if !(0.0..=1.0).contains(&u)
+        {
+            return None;
+        }
+
+        let q = s.cross(edge1);
+        let v = self.direction.dot(q) * inverse_det;
+        if v < 0.0 || u + v > 1.0
+        {
+            return None;
+        }
+
+        let t = edge2.dot(q) * inverse_det;
+        if t < 0.0
+        {
+            return None;
+        }
+
+        return Some((t, Vec3::new(1.0 - u - v, u, v)));
+    }
+}
+
+impl Camera
+{
+    // world space ray through a pixel, pixel coordinates as used by the rasterizer
+    pub fn screen_ray(&self, pixel: Vec2, viewport: (usize, usize)) -> Ray
+    {
+        let ndc = Vec2::new(pixel.x / viewport.0 as f32 * 2.0 - 1.0, 1.0 - pixel.y / viewport.1 as f32 * 2.0);
+        let inverse = self.view_projection().inverse();
+
+        let near = inverse * ndc.extend(0.0).extend(1.0);
+        let far = inverse * ndc.extend(1.0).extend(1.0);
+
+        let origin = near.xyz() / near.w;
+        return Ray { origin, direction: (far.xyz() / far.w - origin).normalize() };
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit
+{
+    // index into the slice passed to pick
+    pub mesh: usize,
+    pub triangle: usize,
+    pub barycentric: Vec3,
+    pub point: Vec3,
+    // world space distance along the ray
+    pub distance: f32,
+}
+
+// closest hit of a world space ray against posed meshes
+pub fn pick(ray: &Ray, meshes: &[(&Mesh, &Transform)]) -> Option<RayHit>
+{
+    let mut closest: Option<RayHit> = None;
+
+    for (m_i, (mesh, transform)) in meshes.iter().enumerate()
+    {
+        let model = transform.to_matrix();
+        let inverse = model.inverse();
+        let local_ray = Ray { origin: inverse.transform_point3(ray.origin), direction: inverse.transform_vector3(ray.direction) };
+
+        if !mesh.is_deformed() && local_ray.intersect_aabb(&mesh.aabb).is_none()
+        {
+            continue;
+        }
+
+        let vertices = mesh.deformed_vertices();
+
+        for (t_i, indices) in mesh.indices.iter().enumerate()
+        {
+            let v0 = vertices[indices.x as usize].pos.xyz();
+            let v1 = vertices[indices.y as usize].pos.xyz();
+            let v2 = vertices[indices.z as usize].pos.xyz();
+
+            if let Some((_, barycentric)) = local_ray.intersect_triangle(v0, v1, v2)
+            {
+                let point = model.transform_point3(v0 * barycentric.x + v1 * barycentric.y + v2 * barycentric.z);
+                let distance = point.distance(ray.origin);
+
+                if closest.is_none_or(|hit| distance < hit.distance)
+                {
+                    closest = Some(RayHit { mesh: m_i, triangle: t_i, barycentric, point, distance });
+                }
+            }
+        }
+    }
+
+    return closest;
+}
@@ -0,0 +1,473 @@
+// screen space anti-aliasing over the final display colors, a cheap alternative to multisampling;
+// Renderer::end_frame runs the chain after tonemapping, so overlays drawn afterwards stay sharp
+
+use glam::{Vec2, Vec3};
+use std::ops::{Add, Mul};
+
+use crate::color::{decode_srgb_u8, from_linear_rgb};
+
+// fxaa 3.11 quality preset
+const FXAA_EDGE_THRESHOLD_MIN: f32 = 0.0312;
+const FXAA_EDGE_THRESHOLD_MAX: f32 = 0.125;
+const FXAA_SUBPIXEL_QUALITY: f32 = 0.75;
+// step sizes along the edge while searching for its ends, in pixels
+const FXAA_SEARCH_STEPS: [f32; 12] = [1.0, 1.0, 1.0, 1.0, 1.0, 1.5, 2.0, 2.0, 2.0, 2.0, 4.0, 8.0];
+
+// smaa luma edge detection, an edge is dropped when a neighbouring one is this many times stronger
+const SMAA_THRESHOLD: f32 = 0.1;
+const SMAA_LOCAL_CONTRAST_FACTOR: f32 = 2.0;
+
+// a pixel's edges are with its left and top neighbours, the right and bottom ones belong to the next pixels
+const EDGE_LEFT: u8 = 1;
+const EDGE_TOP: u8 = 2;
+
+// how much of each neighbour a pixel blends in
+const BLEND_TOP: usize = 0;
+const BLEND_BOTTOM: usize = 1;
+const BLEND_LEFT: usize = 2;
+const BLEND_RIGHT: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PostEffect
+{
+    Fxaa,
+    // edge detection and blending weights as in smaa 1x, with the areas computed
+    // analytically instead of read from the precomputed area texture
+    Smaa,
+}
+
+impl PostEffect
+{
+    pub const ALL: [PostEffect; 2] = [PostEffect::Fxaa, PostEffect::Smaa];
+
+    pub fn name(&self) -> &'static str
+    {
+        return match self
+        {
+            PostEffect::Fxaa => "fxaa",
+            PostEffect::Smaa => "smaa",
+        };
+    }
+
+    pub fn from_name(name: &str) -> Option<PostEffect>
+    {
+        return PostEffect::ALL.iter().find(|effect| effect.name() == name).copied();
+    }
+}
+
+pub struct PostChain
+{
+    // run in order, each effect works on what the previous one produced
+    pub effects: Vec<PostEffect>,
+    // the input of the running effect, kept to reuse the allocations
+    source: Vec<u32>,
+    linear: Vec<Vec3>,
+    luma: Vec<f32>,
+    edges: Vec<u8>,
+    weights: Vec<[f32; 4]>,
+}
+
+impl PostChain
+{
+    pub fn new() -> Self
+    {
+        return Self { effects: Vec::new(), source: Vec::new(), linear: Vec::new(), luma: Vec::new(), edges: Vec::new(), weights: Vec::new() };
+    }
+
+    pub fn with_effects(effects: &[PostEffect]) -> Self
+    {
+        let mut chain = Self::new();
+        chain.effects = effects.to_vec();
+
+        return chain;
+    }
+
+    // the effect names joined with +, or none
+    pub fn name(&self) -> String
+    {
+        if self.effects.is_empty()
+        {
+            return "none".to_string();
+        }
+
+        return self.effects.iter().map(|effect| effect.name()).collect::<Vec<_>>().join("+");
+    }
+
+    pub fn apply(&mut self, color: &mut [u32], viewport: (usize, usize))
+    {
+        assert_eq!(color.len(), viewport.0 * viewport.1, "color buffer doesn't match the viewport");
+
+        for e_i in 0..self.effects.len()
+        {
+            // each effect reads from a copy and writes its result straight back into `color`
+            self.source.clear();
+            self.source.extend_from_slice(color);
+
+            self.linear.clear();
+            self.linear.extend(self.source.iter().map(|pixel| decode_pixel(*pixel)));
+
+            self.luma.clear();
+            self.luma.extend(self.source.iter().map(|pixel| luma(*pixel)));
+
+            match self.effects[e_i]
+            {
+                PostEffect::Fxaa => fxaa(&self.source, &self.linear, &self.luma, color, viewport),
+
+                PostEffect::Smaa =>
+                {
+                    smaa_detect_edges(&self.luma, &mut self.edges, viewport);
+                    smaa_blending_weights(&self.edges, &mut self.weights, viewport);
+                    smaa_blend(&self.source, &self.linear, &self.weights, color, viewport);
+                }
+            }
+        }
+    }
+}
+
+impl Default for PostChain
+{
+    fn default() -> Self
+    {
+        return Self::new();
+    }
+}
+
+fn decode_pixel(pixel: u32) -> Vec3
+{
+    let [_, red, green, blue] = pixel.to_be_bytes();
+
+    return Vec3::new(decode_srgb_u8(red), decode_srgb_u8(green), decode_srgb_u8(blue));
+}
+
+// perceptual, taken from the encoded values like both techniques expect
+fn luma(pixel: u32) -> f32
+{
+    let [_, red, green, blue] = pixel.to_be_bytes();
+
+    return (0.2126 * red as f32 + 0.7152 * green as f32 + 0.0722 * blue as f32) / 255.0;
+}
+
+// pixel centers sit on integer coordinates, positions outside the image are clamped to its border
+fn bilinear<T>(buffer: &[T], viewport: (usize, usize), p: Vec2) -> T
+where
+    T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+{
+    let p = p.clamp(Vec2::ZERO, Vec2::new(viewport.0 as f32 - 1.0, viewport.1 as f32 - 1.0));
+
+    let x0 = p.x as usize;
+    let y0 = p.y as usize;
+    let x1 = (x0 + 1).min(viewport.0 - 1);
+    let y1 = (y0 + 1).min(viewport.1 - 1);
+    let fx = p.x - x0 as f32;
+    let fy = p.y - y0 as f32;
+
+    let top = buffer[y0 * viewport.0 + x0] * (1.0 - fx) + buffer[y0 * viewport.0 + x1] * fx;
+    let bottom = buffer[y1 * viewport.0 + x0] * (1.0 - fx) + buffer[y1 * viewport.0 + x1] * fx;
+
+    return top * (1.0 - fy) + bottom * fy;
+}
+
+// Timothy Lottes' fxaa 3.11 quality: find the edge through each high contrast pixel, search along it
+// for both ends and resample the pixel across the edge by how close it is to the nearer end
+fn fxaa(source: &[u32], linear: &[Vec3], luma: &[f32], output: &mut [u32], viewport: (usize, usize))
+{
+    let (width, height) = viewport;
+    let at = |x: usize, y: usize, dx: i32, dy: i32|
+    {
+        let x = (x as i32 + dx).clamp(0, width as i32 - 1) as usize;
+        let y = (y as i32 + dy).clamp(0, height as i32 - 1) as usize;
+        luma[y * width + x]
+    };
+
+    for p_i in 0..(width * height)
+    {
+        let (x, y) = (p_i % width, p_i / width);
+
+        let center = luma[p_i];
+        let north = at(x, y, 0, -1);
+        let south = at(x, y, 0, 1);
+        let west = at(x, y, -1, 0);
+        let east = at(x, y, 1, 0);
+
+        let luma_max = center.max(north).max(south).max(west).max(east);
+        let luma_min = center.min(north).min(south).min(west).min(east);
+        let range = luma_max - luma_min;
+
+        if range < FXAA_EDGE_THRESHOLD_MIN.max(luma_max * FXAA_EDGE_THRESHOLD_MAX)
+        {
+            output[p_i] = source[p_i];
+            continue;
+        }
+
+        let north_west = at(x, y, -1, -1);
+        let north_east = at(x, y, 1, -1);
+        let south_west = at(x, y, -1, 1);
+        let south_east = at(x, y, 1, 1);
+
+        // second derivatives across rows and across columns tell a horizontal edge from a vertical one
+        let horizontal = (north_west + south_west - 2.0 * west).abs() + (north + south - 2.0 * center).abs() * 2.0 + (north_east + south_east - 2.0 * east).abs();
+        let vertical = (north_west + north_east - 2.0 * north).abs() + (west + east - 2.0 * center).abs() * 2.0 + (south_west + south_east - 2.0 * south).abs();
+        let is_horizontal = horizontal >= vertical;
+
+        let (across, along) = if is_horizontal { (Vec2::Y, Vec2::X) } else { (Vec2::X, Vec2::Y) };
+        let (negative, positive) = if is_horizontal { (north, south) } else { (west, east) };
+
+        // the edge lies on the side with the steeper gradient
+        let gradient_negative = (negative - center).abs();
+        let gradient_positive = (positive - center).abs();
+        let gradient_scaled = gradient_negative.max(gradient_positive) * 0.25;
+
+        let (step, local_average) = if gradient_negative >= gradient_positive { (-1.0, (negative + center) * 0.5) } else { (1.0, (positive + center) * 0.5) };
+
+        let point = Vec2::new(x as f32, y as f32);
+        let on_edge = point + across * step * 0.5;
+
+        let mut end_negative = on_edge - along * FXAA_SEARCH_STEPS[0];
+        let mut end_positive = on_edge + along * FXAA_SEARCH_STEPS[0];
+        let mut luma_negative = bilinear(luma, viewport, end_negative) - local_average;
+        let mut luma_positive = bilinear(luma, viewport, end_positive) - local_average;
+        let mut reached_negative = luma_negative.abs() >= gradient_scaled;
+        let mut reached_positive = luma_positive.abs() >= gradient_scaled;
+
+        for step_size in &FXAA_SEARCH_STEPS[1..]
+        {
+            if reached_negative && reached_positive
+            {
+                break;
+            }
+
+            if !reached_negative
+            {
+                end_negative -= along * *step_size;
+                luma_negative = bilinear(luma, viewport, end_negative) - local_average;
+                reached_negative = luma_negative.abs() >= gradient_scaled;
+            }
+
+            if !reached_positive
+            {
+                end_positive += along * *step_size;
+                luma_positive = bilinear(luma, viewport, end_positive) - local_average;
+                reached_positive = luma_positive.abs() >= gradient_scaled;
+            }
+        }
+
+        let distance_negative = along.dot(point - end_negative);
+        let distance_positive = along.dot(end_positive - point);
+
+        let (distance, luma_end) = if distance_negative < distance_positive { (distance_negative, luma_negative) } else { (distance_positive, luma_positive) };
+        let edge_length = distance_negative + distance_positive;
+
+        // only move when the nearer end goes the other way from the center, otherwise this pixel is past the edge's step
+        let edge_offset = if (luma_end < 0.0) != (center < local_average) { 0.5 - distance / edge_length } else { 0.0 };
+
+        // isolated pixels and thin lines get smoothed by how much they stand out from the neighbourhood
+        let average = ((north + south + west + east) * 2.0 + north_west + north_east + south_west + south_east) / 12.0;
+        let subpixel = ((average - center).abs() / range).clamp(0.0, 1.0);
+        let subpixel = (-2.0 * subpixel + 3.0) * subpixel * subpixel;
+        let subpixel_offset = subpixel * subpixel * FXAA_SUBPIXEL_QUALITY;
+
+        let offset = edge_offset.max(subpixel_offset);
+        output[p_i] = from_linear_rgb(bilinear(linear, viewport, point + across * step * offset));
+    }
+}
+
+// marks the left and top edges whose luma difference is above the threshold and not dwarfed by a nearby edge
+fn smaa_detect_edges(luma: &[f32], edges: &mut Vec<u8>, viewport: (usize, usize))
+{
+    let (width, height) = viewport;
+
+    edges.clear();
+    edges.resize(width * height, 0);
+
+    // difference to the pixel dx, dy away, zero across the border
+    let delta = |x: usize, y: usize, dx: i32, dy: i32|
+    {
+        let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+        if nx < 0 || ny < 0 || nx >= width as i32 || ny >= height as i32
+        {
+            return 0.0;
+        }
+        (luma[y * width + x] - luma[ny as usize * width + nx as usize]).abs()
+    };
+
+    for (p_i, edge) in edges.iter_mut().enumerate()
+    {
+        let (x, y) = (p_i % width, p_i / width);
+
+        let left = delta(x, y, -1, 0);
+        let top = delta(x, y, 0, -1);
+
+        if left < SMAA_THRESHOLD && top < SMAA_THRESHOLD
+        {
+            continue;
+        }
+
+        let right = delta(x, y, 1, 0);
+        let bottom = delta(x, y, 0, 1);
+        let left_left = if x > 0 { delta(x - 1, y, -1, 0) } else { 0.0 };
+        let top_top = if y > 0 { delta(x, y - 1, 0, -1) } else { 0.0 };
+
+        let strongest = left.max(top).max(right).max(bottom).max(left_left).max(top_top);
+
+        if left >= SMAA_THRESHOLD && left * SMAA_LOCAL_CONTRAST_FACTOR >= strongest
+        {
+            *edge |= EDGE_LEFT;
+        }
+
+        if top >= SMAA_THRESHOLD && top * SMAA_LOCAL_CONTRAST_FACTOR >= strongest
+        {
+            *edge |= EDGE_TOP;
+        }
+    }
+}
+
+// follows every run of edges to both of its ends and classifies them by the crossing edges found there,
+// each end with a crossing edge bends the reconstructed silhouette half a pixel towards that side
+fn smaa_blending_weights(edges: &[u8], weights: &mut Vec<[f32; 4]>, viewport: (usize, usize))
+{
+    let (width, height) = viewport;
+
+    weights.clear();
+    weights.resize(width * height, [0.0; 4]);
+
+    // +1 when only the crossing edge on the first side exists, -1 for the second side, 0 for neither or both
+    let side = |first: bool, second: bool| match (first, second)
+    {
+        (true, false) => 1.0,
+        (false, true) => -1.0,
+        _ => 0.0,
+    };
+
+    // horizontal edges, between row y - 1 and row y; vertical crossing edges above the run bend it upwards
+    for y in 1..height
+    {
+        let row = y * width;
+        let mut x = 0;
+
+        while x < width
+        {
+            if edges[row + x] & EDGE_TOP == 0
+            {
+                x += 1;
+                continue;
+            }
+
+            let start = x;
+            while x < width && edges[row + x] & EDGE_TOP != 0
+            {
+                x += 1;
+            }
+
+            let crossing = |x: usize| if x < width { side(edges[row - width + x] & EDGE_LEFT != 0, edges[row + x] & EDGE_LEFT != 0) } else { 0.0 };
+            let (start_side, end_side) = (crossing(start), crossing(x));
+
+            for i in 0..(x - start)
+            {
+                let (above, below) = edge_coverage(i, x - start, start_side, end_side);
+                weights[row - width + start + i][BLEND_BOTTOM] += above;
+                weights[row + start + i][BLEND_TOP] += below;
+            }
+        }
+    }
+
+    // vertical edges, between column x - 1 and column x; horizontal crossing edges on the left bend it to the left
+    for x in 1..width
+    {
+        let mut y = 0;
+
+        while y < height
+        {
+            if edges[y * width + x] & EDGE_LEFT == 0
+            {
+                y += 1;
+                continue;
+            }
+
+            let start = y;
+            while y < height && edges[y * width + x] & EDGE_LEFT != 0
+            {
+                y += 1;
+            }
+
+            let crossing = |y: usize| if y < height { side(edges[y * width + x - 1] & EDGE_TOP != 0, edges[y * width + x] & EDGE_TOP != 0) } else { 0.0 };
+            let (start_side, end_side) = (crossing(start), crossing(y));
+
+            for i in 0..(y - start)
+            {
+                let (left, right) = edge_coverage(i, y - start, start_side, end_side);
+                weights[(start + i) * width + x - 1][BLEND_RIGHT] += left;
+                weights[(start + i) * width + x][BLEND_LEFT] += right;
+            }
+        }
+    }
+}
+
+// area between the edge and the reconstructed silhouette over pixel `i` of a run of `length` pixels, split into
+// the parts on the first and the second side; the silhouette leaves each end half a pixel towards `start_side`
+// and `end_side` and meets the edge halfway along the run, as in mlaa
+fn edge_coverage(i: usize, length: usize, start_side: f32, end_side: f32) -> (f32, f32)
+{
+    let half = length as f32 * 0.5;
+    let height = |x: f32| if x < half { start_side * 0.5 * (1.0 - x / half) } else { end_side * 0.5 * (x / half - 1.0) };
+
+    let (from, to) = (i as f32, i as f32 + 1.0);
+    let mut first = 0.0;
+    let mut second = 0.0;
+
+    // straight on either side of the middle, so the midpoint rule is exact for each piece
+    for (a, b) in [(from, to.min(half)), (from.max(half), to)]
+    {
+        if b > a
+        {
+            let area = (b - a) * height((a + b) * 0.5);
+
+            if area > 0.0
+            {
+                first += area;
+            }
+            else
+            {
+                second -= area;
+            }
+        }
+    }
+
+    return (first, second);
+}
+
+fn smaa_blend(source: &[u32], linear: &[Vec3], weights: &[[f32; 4]], output: &mut [u32], viewport: (usize, usize))
+{
+    let width = viewport.0;
+
+    for (p_i, weight) in weights.iter().enumerate()
+    {
+        let horizontal = weight[BLEND_TOP] + weight[BLEND_BOTTOM];
+        let vertical = weight[BLEND_LEFT] + weight[BLEND_RIGHT];
+
+        if horizontal == 0.0 && vertical == 0.0
+        {
+            output[p_i] = source[p_i];
+            continue;
+        }
+
+        // like smaa, only the dominant direction is blended
+        let (neighbours, blend) = if horizontal >= vertical
+        {
+            ([(p_i.wrapping_sub(width), weight[BLEND_TOP]), (p_i + width, weight[BLEND_BOTTOM])], horizontal)
+        }
+        else
+        {
+            ([(p_i.wrapping_sub(1), weight[BLEND_LEFT]), (p_i + 1, weight[BLEND_RIGHT])], vertical)
+        };
+
+        let mut color = linear[p_i] * (1.0 - blend);
+
+        // a weight is only ever set towards a neighbour across a detected edge, so the neighbour exists
+        for (n_i, amount) in neighbours.into_iter().filter(|(_, amount)| *amount > 0.0)
+        {
+            color += linear[n_i] * amount;
+        }
+
+        output[p_i] = from_linear_rgb(color);
+    }
+}
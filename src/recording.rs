@@ -0,0 +1,123 @@
+use glam::Vec2;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::input::{InputFrame, key_from_name};
+
+const HEADER: &str = "rusterizer-input 2";
+// same lines without the render size
+const HEADER_V1: &str = "rusterizer-input 1";
+
+// every frame of input of a session, replayed with the same fixed timestep it was recorded with
+#[derive(Clone, Debug)]
+pub struct InputRecording
+{
+    // seconds of simulation per frame
+    pub timestep: f32,
+    pub frames: Vec<InputFrame>,
+    // render resolution of each frame, mouse positions are in these pixels;
+    // empty for version 1 recordings, which replay at the configured size
+    pub render_sizes: Vec<(usize, usize)>,
+}
+
+impl InputRecording
+{
+    pub fn new(timestep: f32) -> InputRecording
+    {
+        return InputRecording { timestep, frames: Vec::new(), render_sizes: Vec::new() };
+    }
+
+    pub fn push(&mut self, frame: &InputFrame, render_size: (usize, usize))
+    {
+        self.frames.push(frame.clone());
+        self.render_sizes.push(render_size);
+    }
+
+    // plain text, a header, the timestep and then one frame per line: render size as
+    // <width>x<height>, mouse x, mouse y, buttons as 0/1 left middle right, wheel, comma separated keys or -
+    pub fn save(&self, path: &Path) -> std::io::Result<()>
+    {
+        let mut text = format!("{}\n{}\n", HEADER, self.timestep);
+
+        for (frame, (width, height)) in self.frames.iter().zip(&self.render_sizes)
+        {
+            let mut frame = frame.clone();
+            frame.retain_known_keys();
+
+            let buttons: String = frame.mouse_buttons.iter().map(|down| if *down { '1' } else { '0' }).collect();
+            let keys = if frame.keys.is_empty() { "-".to_string() } else { frame.keys.iter().map(|key| format!("{:?}", key)).collect::<Vec<_>>().join(",") };
+
+            let _ = writeln!(text, "{}x{} {} {} {} {} {}", width, height, frame.mouse_pos.x, frame.mouse_pos.y, buttons, frame.wheel, keys);
+        }
+
+        return std::fs::write(path, text);
+    }
+
+    pub fn load(path: &Path) -> Result<InputRecording, String>
+    {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let error = |l_i: usize, message: &str| format!("{}:{}: {}", path.display(), l_i + 1, message);
+
+        let mut lines = text.lines().enumerate();
+
+        let has_sizes = match lines.next().map(|(_, line)| line.trim())
+        {
+            Some(HEADER) => true,
+            Some(HEADER_V1) => false,
+            _ => return Err(error(0, "not an input recording")),
+        };
+
+        let (l_i, timestep) = lines.next().ok_or(error(1, "missing timestep"))?;
+        let timestep: f32 = timestep.trim().parse().map_err(|_| error(l_i, "bad timestep"))?;
+
+        let mut recording = InputRecording::new(timestep);
+
+        for (l_i, line) in lines
+        {
+            let mut fields: Vec<&str> = line.split_whitespace().collect();
+            let field_count = if has_sizes { 6 } else { 5 };
+            if fields.len() != field_count
+            {
+                return Err(error(l_i, &format!("expected {} fields", field_count)));
+            }
+
+            if has_sizes
+            {
+                let size = fields.remove(0);
+                let size = size.split_once('x').and_then(|(w, h)| Some((w.parse::<usize>().ok()?, h.parse::<usize>().ok()?)));
+                match size
+                {
+                    Some((width, height)) if width > 0 && height > 0 => recording.render_sizes.push((width, height)),
+                    _ => return Err(error(l_i, "bad render size")),
+                }
+            }
+
+            let number = |field: &str| field.parse::<f32>().map_err(|_| error(l_i, &format!("bad number '{}'", field)));
+
+            let buttons: Vec<char> = fields[2].chars().collect();
+            if buttons.len() != 3 || buttons.iter().any(|c| *c != '0' && *c != '1')
+            {
+                return Err(error(l_i, "bad mouse buttons"));
+            }
+
+            let mut keys = Vec::new();
+            if fields[4] != "-"
+            {
+                for name in fields[4].split(',')
+                {
+                    keys.push(key_from_name(name).ok_or(error(l_i, &format!("unknown key '{}'", name)))?);
+                }
+            }
+
+            recording.frames.push(InputFrame
+            {
+                keys,
+                mouse_pos: Vec2::new(number(fields[0])?, number(fields[1])?),
+                mouse_buttons: [buttons[0] == '1', buttons[1] == '1', buttons[2] == '1'],
+                wheel: number(fields[3])?,
+            });
+        }
+
+        return Ok(recording);
+    }
+}
@@ -0,0 +1,307 @@
+use glam::{Mat3, Mat4, Vec2, Vec4, Vec4Swizzles};
+use std::time::Instant;
+
+use crate::bounds::Frustum;
+use crate::camera::Camera;
+use crate::debug::{DebugMode, DebugView};
+use crate::environment::{Environment, draw_background};
+use crate::framebuffer::Framebuffer;
+use crate::geometry::{Mesh, Vertex};
+use crate::hiz::DepthPyramid;
+use crate::picking::PickId;
+use crate::postprocess::PostChain;
+use crate::stats::RenderStats;
+use crate::tonemap::Tonemap;
+use crate::transform::Transform;
+use crate::{clip_triangle, rasterize_triangle};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CullMode
+{
+    None,
+    // counter-clockwise triangles face the camera, as in gltf
+    Back,
+    Front,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PipelineState
+{
+    pub cull_mode: CullMode,
+    // test meshes and triangles against the depth pyramid, see Renderer::build_depth_pyramid
+    pub occlusion_culling: bool,
+    // written to the framebuffer's id buffer, if it has one
+    pub object_id: u32,
+}
+
+impl Default for PipelineState
+{
+    fn default() -> Self
+    {
+        return Self { cull_mode: CullMode::None, occlusion_culling: false, object_id: 0 };
+    }
+}
+
+pub struct Renderer
+{
+    pub framebuffer: Framebuffer,
+    pub debug: DebugView,
+    pub tonemap: Tonemap,
+    // screen space antialiasing, runs at the end of end_frame
+    pub post_process: PostChain,
+    pub state: PipelineState,
+    pub stats: RenderStats,
+    pub depth_pyramid: DepthPyramid,
+    // post-transform vertices of the mesh being drawn, kept to reuse the allocation
+    projected: Vec<Vertex>,
+    // triangles of the mesh being drawn that survived culling and clipping, same
+    clipped: Vec<([Vertex; 3], PickId)>,
+}
+
+impl Renderer
+{
+    pub fn new(framebuffer: Framebuffer) -> Self
+    {
+        let size = framebuffer.width * framebuffer.height;
+
+        return Self
+        {
+            framebuffer,
+            debug: DebugView::new(DebugMode::Shaded, size),
+            tonemap: Tonemap::default(),
+            post_process: PostChain::new(),
+            state: PipelineState::default(),
+            stats: RenderStats::default(),
+            depth_pyramid: DepthPyramid::new(),
+            projected: Vec::new(),
+            clipped: Vec::new(),
+        };
+    }
+
+    // new render resolution, takes effect from the next begin_frame
+    pub fn resize(&mut self, width: usize, height: usize)
+    {
+        self.framebuffer.resize(width, height);
+        self.debug.resize(width * height);
+        self.depth_pyramid.clear();
+    }
+
+    pub fn begin_frame(&mut self)
+    {
+        self.stats.reset();
+        let start = Instant::now();
+
+        self.framebuffer.clear();
+        self.debug.clear();
+        self.depth_pyramid.clear();
+
+        self.stats.timings.clear += start.elapsed();
+    }
+
+    // snapshot the current depth for occlusion culling, call after drawing the occluders;
+    // later draws only bring depth nearer so the snapshot stays conservative
+    pub fn build_depth_pyramid(&mut self)
+    {
+        self.depth_pyramid.build(&self.framebuffer.pixel_depths(true), self.framebuffer.width, self.framebuffer.height);
+    }
+
+    // runs the vertex stage once per vertex, assembles, culls and clips triangles from the
+    // transformed vertices, then rasterizes whatever is left
+    pub fn draw_mesh(&mut self, mesh: &Mesh, transform: &Transform, camera: &Camera)
+    {
+        let mvp = camera.view_projection() * transform.to_matrix();
+
+        if !Self::is_mesh_visible(mesh, mvp)
+        {
+            self.stats.meshes_culled += 1;
+            return;
+        }
+
+        if self.state.occlusion_culling && !mesh.is_deformed() && self.is_occluded(mesh.aabb.corners().iter().map(|c| mvp * c.extend(1.0)))
+        {
+            self.stats.meshes_occluded += 1;
+            return;
+        }
+
+        let start = Instant::now();
+
+        self.projected.clear();
+        self.projected.extend(mesh.deformed_vertices().iter()
+        .map(|vertex|
+        {
+            let mut vertex = *vertex;
+            vertex.pos = mvp * vertex.pos;
+            vertex
+        }));
+
+        self.stats.meshes_drawn += 1;
+        self.stats.vertices_processed += self.projected.len();
+        self.stats.triangles_submitted += mesh.indices.len();
+
+        let vertex_done = Instant::now();
+        self.stats.timings.vertex += vertex_done - start;
+
+        self.clipped.clear();
+
+        for (t_i, vertex_indices) in mesh.indices.iter().enumerate()
+        {
+            let triangle = [self.projected[vertex_indices.x as usize], self.projected[vertex_indices.y as usize], self.projected[vertex_indices.z as usize]];
+
+            if self.is_culled(&triangle)
+            {
+                self.stats.triangles_culled += 1;
+                continue;
+            }
+
+            if self.state.occlusion_culling && self.is_occluded(triangle.iter().map(|v| v.pos))
+            {
+                self.stats.triangles_occluded += 1;
+                continue;
+            }
+
+            let clipped_vertices = triangle.iter().filter(|v| v.pos.z < 0.0).count();
+            let (triangles, count) = clip_triangle(triangle);
+
+            match (clipped_vertices, count)
+            {
+                (_, 0) => self.stats.triangles_clipped_away += 1,
+                (0, _) => (),
+                (_, 1) => self.stats.triangles_clipped += 1,
+                _ => self.stats.triangles_split += 1,
+            }
+
+            let id = PickId { object: self.state.object_id, triangle: t_i as u32 };
+            self.clipped.extend(triangles[..count].iter().map(|triangle| (*triangle, id)));
+        }
+
+        let setup_done = Instant::now();
+        self.stats.timings.setup += setup_done - vertex_done;
+
+        self.stats.triangles_rasterized += self.clipped.len();
+
+        for (triangle, id) in &self.clipped
+        {
+            let (tested, passed) = rasterize_triangle(*triangle, &mesh.texture, &mut self.framebuffer, &mut self.debug, *id);
+
+            self.stats.pixels_tested += tested;
+            self.stats.pixels_passed += passed;
+        }
+
+        self.stats.timings.raster += setup_done.elapsed();
+    }
+
+    fn is_mesh_visible(mesh: &Mesh, mvp: Mat4) -> bool
+    {
+        if mesh.vertices.is_empty()
+        {
+            return false;
+        }
+
+        if mesh.is_deformed()
+        {
+            return true;
+        }
+
+        let frustum = Frustum::from_matrix(mvp);
+
+        return frustum.intersects_sphere(&mesh.bounding_sphere) && frustum.intersects_aabb(&mesh.aabb);
+    }
+
+    // clip space points in, conservative screen rect and nearest depth tested against the pyramid
+    fn is_occluded<I: Iterator<Item = Vec4>>(&self, points: I) -> bool
+    {
+        if self.depth_pyramid.is_empty()
+        {
+            return false;
+        }
+
+        let (width, height) = self.framebuffer.viewport();
+
+        let mut min = Vec2::splat(f32::INFINITY);
+        let mut max = Vec2::splat(f32::NEG_INFINITY);
+        let mut nearest = f32::INFINITY;
+
+        for point in points
+        {
+            // anything touching the near plane can't be projected safely
+            if point.z < 0.0 || point.w <= 0.0
+            {
+                return false;
+            }
+
+            let ndc = point.xy() / point.w;
+            let screen = Vec2::new((ndc.x + 1.0) * 0.5 * width as f32, (1.0 - ndc.y) * 0.5 * height as f32);
+
+            min = min.min(screen);
+            max = max.max(screen);
+            nearest = nearest.min(point.w);
+        }
+
+        let min = (min.floor().max(Vec2::ZERO)).as_uvec2();
+        let max = (max.ceil().min(Vec2::new(width as f32 - 1.0, height as f32 - 1.0))).as_uvec2();
+
+        if min.x > max.x || min.y > max.y
+        {
+            return false;
+        }
+
+        return self.depth_pyramid.is_occluded((min.x as usize, min.y as usize), (max.x as usize, max.y as usize), nearest);
+    }
+
+    // winding from the homogeneous determinant, valid even for vertices behind the camera
+    fn is_culled(&self, triangle: &[Vertex; 3]) -> bool
+    {
+        if self.state.cull_mode == CullMode::None
+        {
+            return false;
+        }
+
+        let det = Mat3::from_cols(triangle[0].pos.xyw(), triangle[1].pos.xyw(), triangle[2].pos.xyw()).determinant();
+
+        return match self.state.cull_mode
+        {
+            CullMode::Back => det <= 0.0,
+            CullMode::Front => det >= 0.0,
+            CullMode::None => false,
+        };
+    }
+
+    pub fn draw_background(&mut self, environment: &Environment, camera: &Camera)
+    {
+        if self.debug.mode == DebugMode::Shaded
+        {
+            let start = Instant::now();
+            draw_background(&mut self.framebuffer, environment, camera.view_projection());
+            self.stats.timings.background += start.elapsed();
+        }
+    }
+
+    // turns whatever was rendered into displayable colors in framebuffer.color
+    pub fn end_frame(&mut self)
+    {
+        let start = Instant::now();
+
+        self.framebuffer.resolve_samples(self.debug.mode == DebugMode::Shaded);
+
+        if self.debug.mode == DebugMode::Shaded
+        {
+            self.framebuffer.resolve_hdr(&self.tonemap);
+        }
+
+        // the depth views show the nearest sample, taken out of the framebuffer while it reads the depth
+        let mut color = std::mem::take(&mut self.framebuffer.color);
+        self.debug.resolve(&mut color, &self.framebuffer.pixel_depths(false));
+        self.framebuffer.color = color;
+
+        self.stats.timings.resolve += start.elapsed();
+
+        // the debug views are left untouched so they keep showing the raw data
+        if self.debug.mode == DebugMode::Shaded && !self.post_process.effects.is_empty()
+        {
+            let start = Instant::now();
+            let viewport = self.framebuffer.viewport();
+            self.post_process.apply(&mut self.framebuffer.color, viewport);
+            self.stats.timings.post += start.elapsed();
+        }
+    }
+}
@@ -0,0 +1,58 @@
+// resolution scale steps, so jitter in the frame time doesn't reallocate every frame
+const SCALE_STEP: f32 = 0.05;
+// frames to wait after a change before judging the new resolution
+const SETTLE_FRAMES: u32 = 10;
+
+// scales the internal render resolution to hold a target frame time,
+// whatever presents the frame is expected to stretch it to the window
+pub struct DynamicResolution
+{
+    // seconds
+    pub target_frame_time: f32,
+    pub min_scale: f32,
+    pub max_scale: f32,
+    scale: f32,
+    frame_time: f32,
+    frames_since_change: u32,
+}
+
+impl DynamicResolution
+{
+    pub fn new(target_frame_time: f32, min_scale: f32) -> Self
+    {
+        return Self { target_frame_time, min_scale, max_scale: 1.0, scale: 1.0, frame_time: 0.0, frames_since_change: 0 };
+    }
+
+    // fraction of the full resolution along each axis
+    pub fn scale(&self) -> f32
+    {
+        return self.scale;
+    }
+
+    // feed the seconds spent rendering the last frame, returns true if the scale changed
+    pub fn update(&mut self, frame_time: f32) -> bool
+    {
+        self.frame_time = if self.frame_time == 0.0 { frame_time } else { self.frame_time * 0.9 + frame_time * 0.1 };
+        self.frames_since_change += 1;
+
+        if self.frames_since_change < SETTLE_FRAMES || self.frame_time <= 0.0
+        {
+            return false;
+        }
+
+        // cost follows the pixel count, which goes with the square of the scale
+        let wanted = self.scale * (self.target_frame_time / self.frame_time).sqrt();
+        let quantized = ((wanted / SCALE_STEP).round() * SCALE_STEP).clamp(self.min_scale, self.max_scale);
+
+        if (quantized - self.scale).abs() < SCALE_STEP * 0.5
+        {
+            return false;
+        }
+
+        self.scale = quantized;
+        self.frame_time = 0.0;
+        self.frames_since_change = 0;
+
+        return true;
+    }
+}
@@ -0,0 +1,176 @@
+use glam::{Mat4, Quat, Vec3};
+
+use crate::animation::Property;
+use crate::geometry::Vertex;
+
+pub struct Joint
+{
+    pub name: Option<String>,
+    // gltf node this joint was loaded from
+    pub node: usize,
+    // index into Skin::joints, None for joints at the top of the skeleton
+    pub parent: Option<usize>,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+    pub inverse_bind_matrix: Mat4,
+}
+
+impl Joint
+{
+    pub fn local_matrix(&self) -> Mat4
+    {
+        return Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation);
+    }
+}
+
+pub struct Skin
+{
+    pub joints: Vec<Joint>,
+    // global transform of whatever non-joint nodes sit above the skeleton
+    pub root: Mat4,
+    // global joint transform * inverse bind matrix, refreshed by update
+    pub joint_matrices: Vec<Mat4>,
+}
+
+impl Skin
+{
+    pub fn load_from_gltf(document: &gltf::Document, skin: &gltf::Skin, buffers: &[gltf::buffer::Data]) -> Skin
+    {
+        let mut node_parents = vec![None; document.nodes().count()];
+        for node in document.nodes()
+        {
+            for child in node.children()
+            {
+                node_parents[child.index()] = Some(node.index());
+            }
+        }
+
+        let joint_nodes: Vec<gltf::Node> = skin.joints().collect();
+
+        let reader = skin.reader(|buffer| Some(&buffers[buffer.index()]));
+        let inverse_bind_matrices: Vec<Mat4> = match reader.read_inverse_bind_matrices()
+        {
+            Some(matrices) => matrices.map(|m| Mat4::from_cols_array_2d(&m)).collect(),
+            None => vec![Mat4::IDENTITY; joint_nodes.len()],
+        };
+
+        let joints: Vec<Joint> = joint_nodes.iter().enumerate()
+        .map(|(i, node)|
+        {
+            let (translation, rotation, scale) = node.transform().decomposed();
+            let parent = node_parents[node.index()].and_then(|p| joint_nodes.iter().position(|j| j.index() == p));
+
+            Joint
+            {
+                name: node.name().map(|n| n.to_string()),
+                node: node.index(),
+                parent,
+                translation: Vec3::from(translation),
+                rotation: Quat::from_array(rotation),
+                scale: Vec3::from(scale),
+                inverse_bind_matrix: inverse_bind_matrices[i],
+            }
+        })
+        .collect();
+
+        // walk up from the first root joint through the non-joint ancestors
+        let mut root = Mat4::IDENTITY;
+        if let Some(top) = joints.iter().find(|j| j.parent.is_none())
+        {
+            let mut ancestor = node_parents[top.node];
+            while let Some(node_index) = ancestor
+            {
+                let node = document.nodes().nth(node_index).unwrap();
+                root = Mat4::from_cols_array_2d(&node.transform().matrix()) * root;
+                ancestor = node_parents[node_index];
+            }
+        }
+
+        let mut result = Skin { joint_matrices: vec![Mat4::IDENTITY; joints.len()], joints, root };
+        result.update();
+
+        return result;
+    }
+
+    // writes an animated value to the joint loaded from `node`, returns false if there is none
+    pub fn set_joint_property(&mut self, node: usize, property: &Property) -> bool
+    {
+        let joint = match self.joints.iter_mut().find(|j| j.node == node)
+        {
+            Some(joint) => joint,
+            None => return false,
+        };
+
+        match property
+        {
+            Property::Translation(translation) => joint.translation = *translation,
+            Property::Rotation(rotation) => joint.rotation = *rotation,
+            Property::Scale(scale) => joint.scale = *scale,
+            Property::MorphWeights(_) => return false,
+        }
+
+        return true;
+    }
+
+    // recompute joint matrices after joint transforms changed
+    pub fn update(&mut self)
+    {
+        let mut globals: Vec<Option<Mat4>> = vec![None; self.joints.len()];
+
+        for i in 0..self.joints.len()
+        {
+            let global = self.global_matrix(i, &mut globals);
+            self.joint_matrices[i] = global * self.joints[i].inverse_bind_matrix;
+        }
+    }
+
+    fn global_matrix(&self, joint: usize, globals: &mut [Option<Mat4>]) -> Mat4
+    {
+        if let Some(global) = globals[joint]
+        {
+            return global;
+        }
+
+        let parent = match self.joints[joint].parent
+        {
+            Some(parent) => self.global_matrix(parent, globals),
+            None => self.root,
+        };
+
+        let global = parent * self.joints[joint].local_matrix();
+        globals[joint] = Some(global);
+
+        return global;
+    }
+
+    // linear blend skinning
+    pub fn skin_vertex(&self, vertex: &Vertex) -> Vertex
+    {
+        let mut matrix = Mat4::ZERO;
+        let mut total_weight = 0.0;
+
+        for i in 0..4
+        {
+            let weight = vertex.weights[i];
+            if weight > 0.0
+            {
+                matrix += self.joint_matrices[vertex.joints[i] as usize] * weight;
+                total_weight += weight;
+            }
+        }
+
+        if total_weight == 0.0
+        {
+            return *vertex;
+        }
+
+        matrix *= 1.0 / total_weight;
+
+        let mut skinned = *vertex;
+        skinned.pos = matrix * vertex.pos;
+        skinned.normal = matrix.transform_vector3(vertex.normal).normalize_or_zero();
+
+        return skinned;
+    }
+}
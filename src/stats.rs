@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct RenderStats
+{
+    pub meshes_drawn: usize,
+    // skipped entirely by frustum culling
+    pub meshes_culled: usize,
+    // skipped because the depth pyramid had them hidden
+    pub meshes_occluded: usize,
+    // after skinning and morphing, once per vertex of every drawn mesh
+    pub vertices_processed: usize,
+    pub triangles_submitted: usize,
+    // rejected by face culling
+    pub triangles_culled: usize,
+    pub triangles_occluded: usize,
+    // near plane clipping: entirely behind it, cut down to one triangle, split into two
+    pub triangles_clipped_away: usize,
+    pub triangles_clipped: usize,
+    pub triangles_split: usize,
+    // triangles that reached the rasterizer, after clipping
+    pub triangles_rasterized: usize,
+    // covered pixels that went through the depth test, and those that passed it; samples when multisampling
+    pub pixels_tested: usize,
+    pub pixels_passed: usize,
+    pub timings: StageTimings,
+}
+
+// wall clock time spent in each stage over the frame
+#[derive(Clone, Copy, Default, Debug)]
+pub struct StageTimings
+{
+    // clearing the targets in begin_frame
+    pub clear: Duration,
+    // transforming, skinning and morphing vertices
+    pub vertex: Duration,
+    // assembly, face and occlusion culling, near plane clipping
+    pub setup: Duration,
+    pub raster: Duration,
+    pub background: Duration,
+    // resolving samples, tonemapping and debug views in end_frame
+    pub resolve: Duration,
+    // the post-process chain
+    pub post: Duration,
+}
+
+impl StageTimings
+{
+    pub const NAMES: [&'static str; 7] = ["clear", "vertex", "setup", "raster", "background", "resolve", "post"];
+
+    // in the order of NAMES
+    pub fn stages(&self) -> [Duration; 7]
+    {
+        return [self.clear, self.vertex, self.setup, self.raster, self.background, self.resolve, self.post];
+    }
+
+    pub fn total(&self) -> Duration
+    {
+        return self.stages().iter().sum();
+    }
+}
+
+impl RenderStats
+{
+    pub fn reset(&mut self)
+    {
+        *self = Self::default();
+    }
+
+    pub fn counters(&self) -> [(&'static str, usize); 13]
+    {
+        return
+        [
+            ("meshes_drawn", self.meshes_drawn),
+            ("meshes_culled", self.meshes_culled),
+            ("meshes_occluded", self.meshes_occluded),
+            ("vertices_processed", self.vertices_processed),
+            ("triangles_submitted", self.triangles_submitted),
+            ("triangles_culled", self.triangles_culled),
+            ("triangles_occluded", self.triangles_occluded),
+            ("triangles_clipped_away", self.triangles_clipped_away),
+            ("triangles_clipped", self.triangles_clipped),
+            ("triangles_split", self.triangles_split),
+            ("triangles_rasterized", self.triangles_rasterized),
+            ("pixels_tested", self.pixels_tested),
+            ("pixels_passed", self.pixels_passed),
+        ];
+    }
+
+    // one line json object, the counters followed by the stage timings in milliseconds
+    pub fn to_json(&self) -> String
+    {
+        let counters: Vec<String> = self.counters().iter().map(|(name, value)| format!("\"{}\": {}", name, value)).collect();
+        let timings: Vec<String> = StageTimings::NAMES.iter().zip(self.timings.stages()).map(|(name, time)| format!("\"{}\": {:.4}", name, time.as_secs_f64() * 1000.0)).collect();
+
+        return format!("{{{}, \"timings_ms\": {{{}}}}}", counters.join(", "), timings.join(", "));
+    }
+}
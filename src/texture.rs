@@ -1,43 +1,118 @@
 pub use std::path::Path;
+use glam::{Vec2, Vec4};
 
-use crate::utils::*;
+use crate::color::*;
+
+// color textures are stored in srgb and get decoded to linear on load,
+// data textures (normals, roughness, ...) are used as is
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextureUsage
+{
+    Color,
+    Data,
+}
 
 pub struct Texture
 {
     pub width: usize,
     pub height: usize,
-    pub data: Vec<u32>,
+    pub usage: TextureUsage,
+    // linear rgba
+    pub data: Vec<Vec4>,
 }
 
 impl Texture
 {
     pub fn load(path: &Path) -> Self
+    {
+        return Self::load_with_usage(path, TextureUsage::Color);
+    }
+
+    pub fn load_with_usage(path: &Path, usage: TextureUsage) -> Self
+    {
+        return Self::try_load_with_usage(path, usage).unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    pub fn try_load(path: &Path) -> Result<Self, String>
+    {
+        return Self::try_load_with_usage(path, TextureUsage::Color);
+    }
+
+    pub fn try_load_with_usage(path: &Path, usage: TextureUsage) -> Result<Self, String>
     {
         let decoded_image = stb_image::image::load(path);
         if let stb_image::image::LoadResult::ImageU8(image) = decoded_image
         {
-            let data = (0..image.data.len() / 3)
-            .map(|id| 
+            let decode = |v: u8| match usage
             {
-                from_u8_rgba(
-                    image.data[id * 3],
-                    image.data[id * 3 + 1],
-                    image.data[id * 3 + 2],
-                    255
-                )
+                TextureUsage::Color => decode_srgb_u8(v),
+                TextureUsage::Data => v as f32 / 255.0,
+            };
+
+            let channels = image.depth;
+
+            let data = (0..image.width * image.height)
+            .map(|id|
+            {
+                let texel = &image.data[id * channels..(id + 1) * channels];
+
+                match channels
+                {
+                    1 | 2 => Vec4::new(decode(texel[0]), decode(texel[0]), decode(texel[0]), if channels == 2 { texel[1] as f32 / 255.0 } else { 1.0 }),
+                    3 => Vec4::new(decode(texel[0]), decode(texel[1]), decode(texel[2]), 1.0),
+                    // alpha is always linear
+                    _ => Vec4::new(decode(texel[0]), decode(texel[1]), decode(texel[2]), texel[3] as f32 / 255.0),
+                }
             })
             .collect();
 
-            return Self
+            return Ok(Self
             {
                 width: image.width,
                 height: image.height,
+                usage,
                 data,
-            };
+            });
         }
-        else 
+        else if let stb_image::image::LoadResult::ImageF32(image) = decoded_image
         {
-            panic!("File not loaded");
+            // float images (.hdr) are already linear and may go above 1.0
+            let channels = image.depth;
+
+            let data = (0..image.width * image.height)
+            .map(|id|
+            {
+                let texel = &image.data[id * channels..(id + 1) * channels];
+
+                match channels
+                {
+                    1 | 2 => Vec4::new(texel[0], texel[0], texel[0], if channels == 2 { texel[1] } else { 1.0 }),
+                    3 => Vec4::new(texel[0], texel[1], texel[2], 1.0),
+                    _ => Vec4::new(texel[0], texel[1], texel[2], texel[3]),
+                }
+            })
+            .collect();
+
+            return Ok(Self
+            {
+                width: image.width,
+                height: image.height,
+                usage,
+                data,
+            });
+        }
+        else
+        {
+            return Err(format!("{}: file not loaded", path.display()));
         }
     }
-}
\ No newline at end of file
+
+    // nearest neighbour lookup with repeat wrapping
+    pub fn sample(&self, uv: Vec2) -> Vec4
+    {
+        let texture_x = ((uv.x * self.width as f32) as isize).rem_euclid(self.width as isize) as usize;
+        let texture_y = ((uv.y * self.height as f32) as isize).rem_euclid(self.height as isize) as usize;
+
+        return self.data[texture_x + texture_y * self.width];
+    }
+}
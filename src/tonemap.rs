@@ -0,0 +1,100 @@
+use glam::Vec3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TonemapOperator
+{
+    Clamp,
+    Reinhard,
+    AcesFilmic,
+    Uncharted2,
+}
+
+impl TonemapOperator
+{
+    pub const ALL: [TonemapOperator; 4] =
+    [
+        TonemapOperator::Clamp,
+        TonemapOperator::Reinhard,
+        TonemapOperator::AcesFilmic,
+        TonemapOperator::Uncharted2,
+    ];
+
+    pub fn name(&self) -> &'static str
+    {
+        return match self
+        {
+            TonemapOperator::Clamp => "clamp",
+            TonemapOperator::Reinhard => "reinhard",
+            TonemapOperator::AcesFilmic => "aces",
+            TonemapOperator::Uncharted2 => "uncharted2",
+        };
+    }
+
+    pub fn next(&self) -> TonemapOperator
+    {
+        let i = TonemapOperator::ALL.iter().position(|op| op == self).unwrap();
+        return TonemapOperator::ALL[(i + 1) % TonemapOperator::ALL.len()];
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Tonemap
+{
+    pub operator: TonemapOperator,
+    // in stops, 0.0 leaves the color untouched
+    pub exposure: f32,
+}
+
+impl Default for Tonemap
+{
+    fn default() -> Self
+    {
+        return Self { operator: TonemapOperator::AcesFilmic, exposure: 0.0 };
+    }
+}
+
+impl Tonemap
+{
+    // linear hdr in, linear 0..1 out
+    pub fn apply(&self, color: Vec3) -> Vec3
+    {
+        let color = color.max(Vec3::ZERO) * self.exposure.exp2();
+
+        let mapped = match self.operator
+        {
+            TonemapOperator::Clamp => color,
+            TonemapOperator::Reinhard => color / (color + Vec3::ONE),
+            TonemapOperator::AcesFilmic => aces_filmic(color),
+            TonemapOperator::Uncharted2 => uncharted2(color * 2.0) / uncharted2(Vec3::splat(UNCHARTED2_WHITE)),
+        };
+
+        return mapped.clamp(Vec3::ZERO, Vec3::ONE);
+    }
+}
+
+// Krzysztof Narkowicz's fit of the ACES reference curve
+fn aces_filmic(x: Vec3) -> Vec3
+{
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+
+    return (x * (a * x + b)) / (x * (c * x + d) + e);
+}
+
+const UNCHARTED2_WHITE: f32 = 11.2;
+
+// John Hable's filmic curve
+fn uncharted2(x: Vec3) -> Vec3
+{
+    let a = 0.15;
+    let b = 0.50;
+    let c = 0.10;
+    let d = 0.20;
+    let e = 0.02;
+    let f = 0.30;
+
+    return ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f;
+}
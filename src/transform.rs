@@ -1,6 +1,8 @@
-use glam::{Vec3, Quat};
+use glam::{Mat4, Vec3, Quat};
 
-#[derive(Clone, Copy)]
+use crate::animation::Property;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Transform
 {
     pub translation: Vec3,
@@ -8,6 +10,14 @@ pub struct Transform
     pub scale: Vec3,
 }
 
+impl Default for Transform
+{
+    fn default() -> Self
+    {
+        return Self::IDENTITY;
+    }
+}
+
 impl Transform
 {
     pub const IDENTITY: Self = Self
@@ -16,4 +26,62 @@ impl Transform
         rotation: Quat::IDENTITY,
         scale: Vec3::ONE,
     };
-}
\ No newline at end of file
+
+    pub fn from_translation(translation: Vec3) -> Self
+    {
+        return Self { translation, ..Self::IDENTITY };
+    }
+
+    pub fn to_matrix(&self) -> Mat4
+    {
+        return Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation);
+    }
+
+    // exact only for uniform scale, use inverse_matrix otherwise
+    pub fn inverse(&self) -> Self
+    {
+        let scale = self.scale.recip();
+        let rotation = self.rotation.conjugate();
+        let translation = -(rotation * (self.translation * scale));
+
+        return Self { translation, rotation, scale };
+    }
+
+    pub fn inverse_matrix(&self) -> Mat4
+    {
+        return self.to_matrix().inverse();
+    }
+
+    // parent.mul_transform(child) places child inside parent, exact for uniform parent scale
+    pub fn mul_transform(&self, child: &Transform) -> Self
+    {
+        return Self
+        {
+            translation: self.translation + self.rotation * (self.scale * child.translation),
+            rotation: self.rotation * child.rotation,
+            scale: self.scale * child.scale,
+        };
+    }
+
+    pub fn lerp(&self, other: &Transform, t: f32) -> Self
+    {
+        return Self
+        {
+            translation: self.translation.lerp(other.translation, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+            scale: self.scale.lerp(other.scale, t),
+        };
+    }
+
+    // applies an animated value, morph weights live on the mesh and are ignored here
+    pub fn set_property(&mut self, property: &Property)
+    {
+        match property
+        {
+            Property::Translation(translation) => self.translation = *translation,
+            Property::Rotation(rotation) => self.rotation = *rotation,
+            Property::Scale(scale) => self.scale = *scale,
+            Property::MorphWeights(_) => {}
+        }
+    }
+}
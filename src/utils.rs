@@ -1,10 +1,17 @@
-use glam::Vec2;
+use glam::{Vec2, Vec3};
 
 pub fn from_u8_rgba(red: u8, green: u8, blue: u8, alpha: u8) -> u32
 {
     return u32::from_be_bytes([alpha, red, green, blue]);
 }
 
+pub fn from_vec3_rgb(color: Vec3) -> u32
+{
+    let color = color.clamp(Vec3::ZERO, Vec3::ONE) * 255.0;
+
+    return from_u8_rgba(color.x as u8, color.y as u8, color.z as u8, 255);
+}
+
 pub fn map_to_range<T>(v: T, a1: T, a2: T, b1: T, b2: T) -> T
 where
     T: std::ops::Sub<Output = T>
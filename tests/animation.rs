@@ -0,0 +1,38 @@
+// clips sampled against the transforms they should produce
+
+use glam::{Quat, Vec3};
+
+use rusterizer::*;
+
+#[test]
+fn turntable_keeps_the_rest_rotation()
+{
+    // like the helmet, whose node stands the z-up model upright
+    let rest = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+    let clip = AnimationClip::turntable(3, Vec3::NEG_Y, 4.0, rest);
+
+    let rotation_at = |time: f32|
+    {
+        let mut transform = Transform::IDENTITY;
+        clip.sample(time, |node, property|
+        {
+            assert_eq!(node, 3);
+            transform.set_property(&property);
+        });
+        transform.rotation
+    };
+
+    assert!(rotation_at(0.0).abs_diff_eq(rest, 1e-6), "{} is not the rest rotation at the start", rotation_at(0.0));
+
+    for time in [0.5, 1.0, 2.5, 3.9]
+    {
+        let rotation = rotation_at(time);
+
+        // the model's up axis stays upright while it spins
+        let up = rest.inverse() * Vec3::Y;
+        assert!((rotation * up - Vec3::Y).length() < 1e-5, "up turned to {} at {} s", rotation * up, time);
+
+        let expected = Quat::from_axis_angle(Vec3::NEG_Y, std::f32::consts::TAU * time / 4.0) * rest;
+        assert!(rotation.abs_diff_eq(expected, 1e-4) || rotation.abs_diff_eq(-expected, 1e-4), "{} against {} at {} s", rotation, expected, time);
+    }
+}
@@ -0,0 +1,157 @@
+// multisampling against what the samples of a known triangle should produce
+
+use glam::{Vec2, Vec3, Vec4};
+
+use rusterizer::*;
+
+const WIDTH: usize = 96;
+const HEIGHT: usize = 64;
+
+const ID: PickId = PickId { object: 7, triangle: 0 };
+
+// a white triangle given in screen space, projected straight to clip space
+fn screen_triangle(points: [Vec2; 3], uvs: [Vec2; 3]) -> [Vertex; 3]
+{
+    return [0, 1, 2].map(|i|
+    {
+        let ndc = Vec2::new(points[i].x / WIDTH as f32 * 2.0 - 1.0, 1.0 - points[i].y / HEIGHT as f32 * 2.0);
+        Vertex::new(Vec4::new(ndc.x, ndc.y, 0.5, 1.0), Vec3::Z, Vec3::ONE, uvs[i])
+    });
+}
+
+fn rasterize(triangles: &[[Vertex; 3]], texture: &Option<Texture>, antialiasing: Antialiasing) -> Framebuffer
+{
+    let mut framebuffer = Framebuffer::with_hdr(WIDTH, HEIGHT);
+    framebuffer.set_antialiasing(antialiasing);
+    framebuffer.enable_ids();
+
+    let mut debug = DebugView::new(DebugMode::Shaded, WIDTH * HEIGHT);
+
+    for (t_i, triangle) in triangles.iter().enumerate()
+    {
+        clip_and_rasterize_projected_triangle(*triangle, texture, &mut framebuffer, &mut debug, PickId { triangle: t_i as u32, ..ID });
+    }
+    framebuffer.resolve_samples(true);
+
+    return framebuffer;
+}
+
+fn covered_samples(framebuffer: &Framebuffer, p_i: usize) -> usize
+{
+    let sample_count = framebuffer.sample_count();
+
+    return framebuffer.depth[p_i * sample_count..][..sample_count].iter().filter(|depth| depth.is_finite()).count();
+}
+
+// whether a single triangle owns every sample of the pixel
+fn fully_covered(framebuffer: &Framebuffer, p_i: usize) -> bool
+{
+    let sample_count = framebuffer.sample_count();
+    let ids = &framebuffer.ids.as_ref().unwrap()[p_i * sample_count..][..sample_count];
+
+    return !ids[0].is_none() && ids.iter().all(|id| *id == ids[0]);
+}
+
+// fraction of each pixel's square inside the triangle, supersampled
+fn coverage(points: [Vec2; 3]) -> Vec<f32>
+{
+    let n = 32;
+    let inside = |p: Vec2| (0..3).all(|i| (points[(i + 1) % 3] - points[i]).perp_dot(p - points[i]) * (points[2] - points[0]).perp_dot(points[1] - points[0]) <= 0.0);
+
+    return (0..WIDTH * HEIGHT).map(|p_i|
+    {
+        let (x, y) = ((p_i % WIDTH) as f32, (p_i / WIDTH) as f32);
+        let covered = (0..n * n).filter(|s_i| inside(Vec2::new(x - 0.5 + ((s_i % n) as f32 + 0.5) / n as f32, y - 0.5 + ((s_i / n) as f32 + 0.5) / n as f32))).count();
+
+        covered as f32 / (n * n) as f32
+    }).collect();
+}
+
+// long shallow and steep edges at angles that don't line up with any sample pattern
+const TRIANGLE: [Vec2; 3] = [Vec2::new(10.3, 5.7), Vec2::new(80.6, 12.2), Vec2::new(30.1, 58.4)];
+
+#[test]
+fn edge_pixels_resolve_to_their_coverage()
+{
+    let expected = coverage(TRIANGLE);
+    let triangle = screen_triangle(TRIANGLE, [Vec2::ZERO; 3]);
+
+    let mean_error = |framebuffer: &Framebuffer| framebuffer.hdr.as_ref().unwrap().iter().zip(&expected).map(|(pixel, coverage)| (pixel.x - coverage).abs()).sum::<f32>() / expected.len() as f32;
+    let aliased = mean_error(&rasterize(&[triangle], &None, Antialiasing::None));
+
+    let mut previous_error = aliased;
+
+    for antialiasing in [Antialiasing::Msaa(2), Antialiasing::Msaa(4), Antialiasing::Msaa(8)]
+    {
+        let framebuffer = rasterize(&[triangle], &None, antialiasing);
+        let sample_count = framebuffer.sample_count();
+        let hdr = framebuffer.hdr.as_ref().unwrap();
+
+        // each pixel is exactly the share of its samples the triangle covers
+        for (p_i, pixel) in hdr.iter().enumerate()
+        {
+            let resolved = covered_samples(&framebuffer, p_i) as f32 / sample_count as f32;
+            assert!((*pixel - Vec4::splat(resolved)).abs().max_element() < 1e-6, "{} at pixel {:?} resolved to {}, expected {}", antialiasing.name(), (p_i % WIDTH, p_i / WIDTH), pixel, resolved);
+        }
+
+        assert!(hdr.iter().any(|pixel| pixel.x > 0.0 && pixel.x < 1.0), "{} left every edge aliased", antialiasing.name());
+
+        // and more samples get closer to the exact coverage
+        let error = mean_error(&framebuffer);
+        assert!(error < previous_error * 0.8, "{}: error {} against {} with fewer samples", antialiasing.name(), error, previous_error);
+        previous_error = error;
+    }
+}
+
+#[test]
+fn interior_pixels_match_the_aliased_render()
+{
+    // a textured quad, multisampling still shades once per pixel at its center
+    let corners = [Vec2::new(12.4, 8.2), Vec2::new(84.7, 4.9), Vec2::new(6.6, 57.3), Vec2::new(79.2, 60.1)];
+    let uvs = [Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(0.0, 1.0), Vec2::new(1.0, 1.0)];
+    let quad = [[0, 1, 2], [1, 3, 2]].map(|[a, b, c]| screen_triangle([corners[a], corners[b], corners[c]], [uvs[a], uvs[b], uvs[c]]));
+
+    let texture = Some(Texture::load(std::path::Path::new("assets/test.jpg")));
+    let aliased = rasterize(&quad, &texture, Antialiasing::None);
+
+    for antialiasing in [Antialiasing::Msaa(2), Antialiasing::Msaa(4), Antialiasing::Msaa(8)]
+    {
+        let framebuffer = rasterize(&quad, &texture, antialiasing);
+        let (hdr, aliased_hdr) = (framebuffer.hdr.as_ref().unwrap(), aliased.hdr.as_ref().unwrap());
+
+        let mut interior = 0;
+
+        for p_i in 0..WIDTH * HEIGHT
+        {
+            let empty = covered_samples(&framebuffer, p_i) == 0;
+            if !empty && !fully_covered(&framebuffer, p_i)
+            {
+                continue;
+            }
+
+            // pixels inside one triangle or outside the quad, the same in both
+            assert!((hdr[p_i] - aliased_hdr[p_i]).abs().max_element() < 1e-5, "{} at pixel {:?} is {}, {} without antialiasing", antialiasing.name(), (p_i % WIDTH, p_i / WIDTH), hdr[p_i], aliased_hdr[p_i]);
+            interior += usize::from(!empty);
+        }
+
+        assert!(interior > WIDTH * HEIGHT / 2, "{} covered only {} pixels fully", antialiasing.name(), interior);
+    }
+}
+
+#[test]
+fn partly_covered_pixels_pick_the_triangle()
+{
+    let triangle = screen_triangle(TRIANGLE, [Vec2::ZERO; 3]);
+
+    for antialiasing in [Antialiasing::Msaa(2), Antialiasing::Msaa(4), Antialiasing::Msaa(8)]
+    {
+        let framebuffer = rasterize(&[triangle], &None, antialiasing);
+
+        for p_i in 0..WIDTH * HEIGHT
+        {
+            let expected = if covered_samples(&framebuffer, p_i) > 0 { Some(ID) } else { None };
+
+            assert_eq!(framebuffer.id_at(p_i % WIDTH, p_i / WIDTH), expected, "{} at pixel {:?}", antialiasing.name(), (p_i % WIDTH, p_i / WIDTH));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+// randomized checks of the near plane clipper against properties that hold for any triangle,
+// proptest shrinks failures to a minimal triangle and records it under proptest-regressions
+
+use glam::{Vec2, Vec3, Vec4};
+use proptest::prelude::*;
+
+use rusterizer::*;
+
+const WIDTH: usize = 64;
+const HEIGHT: usize = 48;
+const NEAR: f32 = 0.1;
+
+// analytic coverage is only trusted where it stays the same this far around the pixel,
+// closer to an edge the rasterizer may legitimately round either way
+const EDGE_MARGIN: f32 = 0.05;
+// likewise for hits this close to the near plane, relative to its distance
+const NEAR_MARGIN: f32 = 1e-4;
+
+fn camera() -> Camera
+{
+    return Camera::new(std::f32::consts::FRAC_PI_2, WIDTH as f32 / HEIGHT as f32, NEAR, 100.0);
+}
+
+// view space points around the camera, some behind it and some exactly on the near plane
+fn view_point() -> impl Strategy<Value = Vec3>
+{
+    let z = prop_oneof![8 => -6.0f32..2.0, 1 => Just(-NEAR)];
+
+    return (-4.0f32..4.0, -4.0f32..4.0, z).prop_map(|(x, y, z)| Vec3::new(x, y, z));
+}
+
+fn unit_vec3() -> impl Strategy<Value = Vec3>
+{
+    return (-1.0f32..1.0, -1.0f32..1.0, -1.0f32..1.0).prop_map(|(x, y, z)| Vec3::new(x, y, z));
+}
+
+// the colors are the unit axes so that a vertex color reads back as its weights over the original vertices
+fn triangle() -> impl Strategy<Value = [Vertex; 3]>
+{
+    let vertex = || (view_point(), unit_vec3(), (0.0f32..1.0, 0.0f32..1.0), (unit_vec3(), -1.0f32..1.0));
+
+    return [vertex(), vertex(), vertex()].prop_map(|vertices|
+    {
+        let colors = [Vec3::X, Vec3::Y, Vec3::Z];
+
+        return [0, 1, 2].map(|i|
+        {
+            let (position, normal, uv, weights) = vertices[i];
+
+            let mut vertex = Vertex::new(position.extend(1.0), normal, colors[i], uv.into());
+            vertex.weights = weights.0.extend(weights.1);
+            vertex
+        });
+    });
+}
+
+fn project(triangle: &[Vertex; 3]) -> [Vertex; 3]
+{
+    let projection = camera().projection();
+
+    return triangle.map(|vertex| Vertex { pos: projection * vertex.pos, ..vertex });
+}
+
+proptest!
+{
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+
+    #[test]
+    fn clipped_triangles_lie_in_front_of_the_near_plane(triangle in triangle())
+    {
+        let projected = project(&triangle);
+        let (triangles, count) = clip_triangle(projected);
+
+        let num_valid = projected.iter().filter(|vertex| vertex.pos.z >= 0.0).count();
+        let expected_count = match num_valid { 0 => 0, 3 => 1, n => n };
+        prop_assert_eq!(count, expected_count);
+
+        for vertex in triangles[..count].iter().flatten()
+        {
+            prop_assert!(vertex.pos.z >= -1e-5, "z = {} is behind the near plane", vertex.pos.z);
+            prop_assert!(vertex.pos.w >= NEAR * 0.999, "w = {} is closer than the near plane", vertex.pos.w);
+        }
+
+        // every vertex that was already valid survives untouched
+        for vertex in projected.iter().filter(|vertex| vertex.pos.z >= 0.0)
+        {
+            prop_assert!(triangles[..count].iter().flatten().any(|v| v.pos == vertex.pos && v.color == vertex.color));
+        }
+    }
+
+    #[test]
+    fn clipped_attributes_are_interpolated_consistently(triangle in triangle())
+    {
+        let projected = project(&triangle);
+        let (triangles, count) = clip_triangle(projected);
+
+        for vertex in triangles[..count].iter().flatten()
+        {
+            // new vertices sit on an edge of the original triangle, so their weights are convex
+            let weights = vertex.color;
+            prop_assert!(weights.min_element() >= -1e-5 && (weights.dot(Vec3::ONE) - 1.0).abs() < 1e-5, "weights {} are not convex", weights);
+
+            // and every attribute is blended with those same weights
+            let blend4 = |f: fn(&Vertex) -> Vec4| f(&projected[0]) * weights.x + f(&projected[1]) * weights.y + f(&projected[2]) * weights.z;
+            let close = |a: Vec4, b: Vec4| (a - b).abs().max_element() <= 1e-4 * a.abs().max_element().max(1.0);
+
+            prop_assert!(close(vertex.pos, blend4(|v| v.pos)), "position {} does not match the weights {}", vertex.pos, weights);
+            prop_assert!(close(vertex.normal.extend(0.0), blend4(|v| v.normal.extend(0.0))), "normal {} does not match the weights {}", vertex.normal, weights);
+            prop_assert!(close(vertex.uv.extend(0.0).extend(0.0), blend4(|v| v.uv.extend(0.0).extend(0.0))), "uv {} does not match the weights {}", vertex.uv, weights);
+            prop_assert!(close(vertex.weights, blend4(|v| v.weights)), "skin weights {} do not match the weights {}", vertex.weights, weights);
+        }
+    }
+
+    #[test]
+    fn coverage_matches_ray_casting(triangle in triangle())
+    {
+        let camera = camera();
+
+        let mut framebuffer = Framebuffer::with_hdr(WIDTH, HEIGHT);
+        let mut debug = DebugView::new(DebugMode::Shaded, WIDTH * HEIGHT);
+        clip_and_rasterize_triangle(triangle, camera.projection(), &None, &mut framebuffer, &mut debug, PickId { object: 0, triangle: 0 });
+
+        let hdr = framebuffer.hdr.as_ref().unwrap();
+        let points = triangle.map(|vertex| vertex.pos.truncate());
+
+        let offsets = [(-1.0, -1.0), (0.0, -1.0), (1.0, -1.0), (-1.0, 0.0), (1.0, 0.0), (-1.0, 1.0), (0.0, 1.0), (1.0, 1.0)];
+
+        for y in 0..HEIGHT
+        {
+            for x in 0..WIDTH
+            {
+                let pixel = Vec2::new(x as f32, y as f32);
+                let hit = ray_cast(&points, &camera, pixel);
+
+                if hit.is_some_and(|(distance, _)| (distance - NEAR).abs() <= NEAR_MARGIN * NEAR)
+                {
+                    continue;
+                }
+
+                let hit = hit.filter(|(distance, _)| *distance >= NEAR);
+                let visible = |pixel: Vec2| ray_cast(&points, &camera, pixel).is_some_and(|(distance, _)| distance >= NEAR);
+
+                if offsets.iter().any(|(dx, dy)| visible(pixel + Vec2::new(*dx, *dy) * EDGE_MARGIN) != hit.is_some())
+                {
+                    continue;
+                }
+
+                let p_i = y * WIDTH + x;
+                let depth = framebuffer.depth[p_i];
+
+                match hit
+                {
+                    None => prop_assert!(depth.is_infinite(), "pixel {}, {} is drawn at depth {} but the triangle does not cover it", x, y, depth),
+
+                    Some((distance, barycentric)) =>
+                    {
+                        prop_assert!(depth.is_finite(), "pixel {}, {} is covered at depth {} but was not drawn", x, y, distance);
+                        prop_assert!((depth - distance).abs() <= 1e-3 * distance, "pixel {}, {} has depth {}, expected {}", x, y, depth, distance);
+
+                        // the vertex colors are the unit axes, so the shaded color is the perspective correct barycentric
+                        let color = hdr[p_i].truncate();
+                        prop_assert!((color - barycentric).abs().max_element() < 1e-2, "pixel {}, {} interpolated {}, expected {}", x, y, color, barycentric);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// found by fuzzing, interpolating from the far end rounded the cut onto the vertex behind the camera
+#[test]
+fn distant_vertices_do_not_pull_the_cut_behind_the_near_plane()
+{
+    let vertex = |x: f32, y: f32, z: f32| Vertex::new(Vec4::new(x, y, z, 1.0), Vec3::Z, Vec3::ONE, Vec2::ZERO);
+    let triangle = [vertex(0.0, 0.0, -4e28), vertex(1.0, 0.0, 0.0), vertex(0.0, 1.0, 0.0)];
+
+    let (triangles, count) = clip_triangle(project(&triangle));
+    assert_eq!(count, 1);
+
+    for vertex in &triangles[0]
+    {
+        assert!(vertex.pos.z >= 0.0 && vertex.pos.w >= NEAR * 0.999, "{} is behind the near plane", vertex.pos);
+    }
+}
+
+// view distance and barycentric coordinates where the ray through a pixel hits the triangle
+// in front of the camera, which sits at the origin looking down -z
+fn ray_cast(points: &[Vec3; 3], camera: &Camera, pixel: Vec2) -> Option<(f32, Vec3)>
+{
+    let ndc = Vec2::new(pixel.x / WIDTH as f32 * 2.0 - 1.0, 1.0 - pixel.y / HEIGHT as f32 * 2.0);
+    let half_height = (camera.fov * 0.5).tan();
+    let direction = Vec3::new(ndc.x * half_height * camera.aspect, ndc.y * half_height, -1.0);
+
+    // möller-trumbore
+    let edge1 = points[1] - points[0];
+    let edge2 = points[2] - points[0];
+
+    let p = direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < 1e-9
+    {
+        return None;
+    }
+
+    let s = -points[0];
+    let u = s.dot(p) / determinant;
+    let q = s.cross(edge1);
+    let v = direction.dot(q) / determinant;
+    // the direction has unit length along -z, so t is the view distance
+    let t = edge2.dot(q) / determinant;
+
+    if u < 0.0 || v < 0.0 || u + v > 1.0 || t <= 0.0
+    {
+        return None;
+    }
+
+    return Some((t, Vec3::new(1.0 - u - v, u, v)));
+}
@@ -0,0 +1,45 @@
+// lines running off the screen, drawn into a small buffer
+
+use rusterizer::draw::draw_line;
+
+const WIDTH: usize = 64;
+const HEIGHT: usize = 32;
+
+const COLOR: u32 = 0xffffff;
+
+fn drawn(buffer: &[u32]) -> Vec<(usize, usize)>
+{
+    return (0..WIDTH * HEIGHT).filter(|p_i| buffer[*p_i] == COLOR).map(|p_i| (p_i % WIDTH, p_i / WIDTH)).collect();
+}
+
+#[test]
+fn lines_leaving_the_screen_are_drawn_up_to_the_edge()
+{
+    // one end on screen, the other far enough away that stepping all of it would take forever
+    let mut buffer = vec![0; WIDTH * HEIGHT];
+    draw_line(&mut buffer, (WIDTH, HEIGHT), (10.0, 10.0), (1e7, 10.0), 1, COLOR);
+
+    assert_eq!(drawn(&buffer), (10..WIDTH).map(|x| (x, 10)).collect::<Vec<_>>());
+
+    // both ends off screen, crossing it on the diagonal
+    let mut buffer = vec![0; WIDTH * HEIGHT];
+    draw_line(&mut buffer, (WIDTH, HEIGHT), (-1e6, -1e6), (1e6, 1e6), 1, COLOR);
+
+    assert_eq!(drawn(&buffer), (0..HEIGHT).map(|i| (i, i)).collect::<Vec<_>>());
+
+    // passing below the screen draws nothing
+    let mut buffer = vec![0; WIDTH * HEIGHT];
+    draw_line(&mut buffer, (WIDTH, HEIGHT), (-1e6, 40.0), (1e6, 40.0), 3, COLOR);
+
+    assert!(drawn(&buffer).is_empty());
+}
+
+#[test]
+fn thick_lines_reach_the_edge_from_just_outside()
+{
+    // the pen is three pixels wide, so a line one pixel above the screen still paints its top row
+    let mut buffer = vec![0; WIDTH * HEIGHT];
+    draw_line(&mut buffer, (WIDTH, HEIGHT), (-1e6, -1.0), (1e6, -1.0), 3, COLOR);
+
+    assert_eq!(drawn(&buffer), (0..WIDTH).map(|x| (x, 0)).collect::<Vec<_>>());
+}
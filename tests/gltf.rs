@@ -0,0 +1,137 @@
+// small gltf files written on the fly, checks what the loader makes of unusual but valid input and of broken files
+
+use glam::Vec3;
+use std::path::PathBuf;
+
+use rusterizer::*;
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_BYTE: u32 = 5121;
+const UNSIGNED_SHORT: u32 = 5123;
+
+struct Accessor
+{
+    // attribute name, or INDICES for the index buffer
+    semantic: &'static str,
+    kind: &'static str,
+    component_type: u32,
+    count: usize,
+    bytes: Vec<u8>,
+}
+
+fn floats(values: &[f32]) -> Vec<u8>
+{
+    return values.iter().flat_map(|v| v.to_le_bytes()).collect();
+}
+
+fn accessor(semantic: &'static str, kind: &'static str, component_type: u32, count: usize, bytes: Vec<u8>) -> Accessor
+{
+    return Accessor { semantic, kind, component_type, count, bytes };
+}
+
+// one triangle in the xy plane facing +z
+fn triangle_positions() -> Accessor
+{
+    return accessor("POSITION", "VEC3", FLOAT, 3, floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]));
+}
+
+fn triangle_indices() -> Accessor
+{
+    return accessor("INDICES", "SCALAR", UNSIGNED_SHORT, 3, [0u16, 1, 2].iter().flat_map(|i| i.to_le_bytes()).collect());
+}
+
+// a single mesh on node 0, `nodes` and `extra` are spliced into the json as they are
+fn write_gltf(name: &str, accessors: &[Accessor], nodes: &str, extra: &str) -> PathBuf
+{
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("gltf");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut buffer = Vec::new();
+    let mut views = Vec::new();
+    let mut accessor_json = Vec::new();
+    let mut attributes = Vec::new();
+    let mut indices = String::new();
+
+    for (a_i, accessor) in accessors.iter().enumerate()
+    {
+        views.push(format!(r#"{{"buffer": 0, "byteOffset": {}, "byteLength": {}}}"#, buffer.len(), accessor.bytes.len()));
+        buffer.extend_from_slice(&accessor.bytes);
+        // every view starts 4 byte aligned
+        buffer.resize(buffer.len().next_multiple_of(4), 0);
+
+        let bounds = if accessor.semantic == "POSITION" { r#", "min": [0, 0, 0], "max": [1, 1, 0]"# } else { "" };
+        accessor_json.push(format!(r#"{{"bufferView": {}, "componentType": {}, "count": {}, "type": "{}"{}}}"#, a_i, accessor.component_type, accessor.count, accessor.kind, bounds));
+
+        match accessor.semantic
+        {
+            "INDICES" => indices = format!(r#", "indices": {}"#, a_i),
+            semantic => attributes.push(format!(r#""{}": {}"#, semantic, a_i)),
+        }
+    }
+
+    std::fs::write(dir.join(format!("{}.bin", name)), &buffer).unwrap();
+
+    let json = format!(
+        r#"{{"asset": {{"version": "2.0"}}, "scene": 0, "scenes": [{{"nodes": [0]}}], "nodes": [{}],
+            "meshes": [{{"primitives": [{{"attributes": {{{}}}{}}}]}}],
+            "buffers": [{{"byteLength": {}, "uri": "{}.bin"}}], "bufferViews": [{}], "accessors": [{}]{}}}"#,
+        nodes, attributes.join(", "), indices, buffer.len(), name, views.join(", "), accessor_json.join(", "), extra);
+
+    let path = dir.join(format!("{}.gltf", name));
+    std::fs::write(&path, json).unwrap();
+
+    return path;
+}
+
+#[test]
+fn missing_normals_are_computed_from_the_faces()
+{
+    let path = write_gltf("no_normals", &[triangle_positions(), triangle_indices()], r#"{"mesh": 0}"#, "");
+    let model = try_load_gltf_model(&path).unwrap();
+
+    assert_eq!(model.mesh.vertices.len(), 3);
+    for vertex in &model.mesh.vertices
+    {
+        assert!((vertex.normal - Vec3::Z).length() < 1e-6, "normal {} of a triangle facing +z", vertex.normal);
+    }
+}
+
+#[test]
+fn short_attributes_and_bad_indices_are_errors()
+{
+    let short_uvs = accessor("TEXCOORD_0", "VEC2", FLOAT, 2, floats(&[0.0; 4]));
+    let path = write_gltf("short_uvs", &[triangle_positions(), short_uvs, triangle_indices()], r#"{"mesh": 0}"#, "");
+    let error = try_load_gltf_model(&path).err().expect("short uvs loaded");
+    assert!(error.contains("TEXCOORD_0"), "{}", error);
+
+    let short_joints = accessor("JOINTS_0", "VEC4", UNSIGNED_BYTE, 1, vec![0; 4]);
+    let path = write_gltf("short_joints", &[triangle_positions(), short_joints, triangle_indices()], r#"{"mesh": 0}"#, "");
+    let error = try_load_gltf_model(&path).err().expect("short joints loaded");
+    assert!(error.contains("JOINTS_0"), "{}", error);
+
+    let bad_indices = accessor("INDICES", "SCALAR", UNSIGNED_SHORT, 3, [0u16, 1, 5].iter().flat_map(|i| i.to_le_bytes()).collect());
+    let path = write_gltf("bad_indices", &[triangle_positions(), bad_indices], r#"{"mesh": 0}"#, "");
+    let error = try_load_gltf_model(&path).err().expect("out of range indices loaded");
+    assert!(error.contains("index 5"), "{}", error);
+}
+
+#[test]
+fn skinned_mesh_nodes_are_placed_by_their_joints_only()
+{
+    let joints = accessor("JOINTS_0", "VEC4", UNSIGNED_BYTE, 3, vec![0; 12]);
+    let weights = accessor("WEIGHTS_0", "VEC4", FLOAT, 3, floats(&[1.0, 0.0, 0.0, 0.0].repeat(3)));
+
+    // the mesh node is moved and turned, the joint lifts the vertices by one
+    let nodes = r#"{"mesh": 0, "skin": 0, "translation": [5, 0, 0], "rotation": [0, 0.7071068, 0, 0.7071068]}, {"translation": [0, 1, 0]}"#;
+    let path = write_gltf("skinned_node", &[triangle_positions(), joints, weights, triangle_indices()], nodes, r#", "skins": [{"joints": [1]}]"#);
+
+    let mut model = try_load_gltf_model(&path).unwrap();
+    model.mesh.skin.as_mut().unwrap().update();
+
+    assert_eq!(model.transform, Transform::IDENTITY);
+
+    let model_matrix = model.transform.to_matrix();
+    let placed: Vec<Vec3> = model.mesh.deformed_vertices().iter().map(|vertex| model_matrix.transform_point3(vertex.pos.truncate())).collect();
+
+    assert_eq!(placed, vec![Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 1.0, 0.0), Vec3::new(0.0, 2.0, 0.0)]);
+}
@@ -0,0 +1,245 @@
+// renders canonical scenes and compares them against the references in tests/golden,
+// run with UPDATE_GOLDEN=1 to (re)write the references after an intentional change
+
+use glam::{Quat, UVec3, Vec2, Vec3, Vec4};
+use std::path::{Path, PathBuf};
+
+use rusterizer::*;
+
+const WIDTH: usize = 160;
+const HEIGHT: usize = 120;
+
+// a pixel counts as different once any channel is off by more than this
+const PIXEL_TOLERANCE: u8 = 8;
+// fraction of pixels allowed to differ before the image fails
+const MAX_DIFFERENT_PIXELS: f32 = 0.002;
+
+#[test]
+fn clipped_triangles()
+{
+    let normal = Vec3::Z;
+
+    let vertex = |x: f32, y: f32, z: f32, color: Vec3| Vertex::new(Vec4::new(x, y, z, 1.0), normal, color, Vec2::ZERO);
+
+    let mut mesh = Mesh
+    {
+        vertices: vec!
+        [
+            // fully visible, interpolated colors
+            vertex(-0.6, 0.4, -5.0, Vec3::X),
+            vertex(0.6, 0.4, -5.0, Vec3::Y),
+            vertex(0.0, 1.4, -5.0, Vec3::Z),
+            // two vertices in front of the near plane, colors interpolate across the cut
+            vertex(-1.0, -1.0, -4.0, Vec3::X),
+            vertex(-1.2, 0.2, -0.5, Vec3::Y),
+            vertex(-2.2, -0.6, -0.5, Vec3::Z),
+            // one vertex in front of the near plane
+            vertex(0.4, -1.2, -4.0, Vec3::X),
+            vertex(1.8, -1.2, -4.0, Vec3::Y),
+            vertex(1.4, 0.2, -0.5, Vec3::Z),
+        ],
+        indices: vec![UVec3::new(0, 1, 2), UVec3::new(3, 4, 5), UVec3::new(6, 7, 8)],
+        ..Mesh::new()
+    };
+    mesh.compute_bounds();
+
+    let camera = Camera::new(std::f32::consts::FRAC_PI_3, WIDTH as f32 / HEIGHT as f32, 1.0, 50.0);
+    let framebuffer = render(&[(&mesh, Transform::IDENTITY)], &camera, DebugMode::Shaded, Antialiasing::None);
+
+    assert_golden("clipped_triangles", &framebuffer);
+}
+
+#[test]
+fn textured_quad()
+{
+    let mut mesh = quad(Texture::load(Path::new("assets/test.jpg")));
+    mesh.compute_bounds();
+
+    // steep enough that affine uvs would visibly bend
+    let transform = Transform
+    {
+        translation: Vec3::new(0.0, 0.0, -3.5),
+        rotation: Quat::from_rotation_y(1.0) * Quat::from_rotation_x(-0.4),
+        scale: Vec3::splat(1.5),
+    };
+
+    let camera = Camera::new(std::f32::consts::FRAC_PI_3, WIDTH as f32 / HEIGHT as f32, 0.5, 50.0);
+
+    assert_golden("textured_quad", &render(&[(&mesh, transform)], &camera, DebugMode::Shaded, Antialiasing::None));
+    assert_golden("textured_quad_uvs", &render(&[(&mesh, transform)], &camera, DebugMode::Uvs, Antialiasing::None));
+    assert_golden("textured_quad_depth", &render(&[(&mesh, transform)], &camera, DebugMode::Depth, Antialiasing::None));
+}
+
+#[test]
+fn antialiased_quad()
+{
+    let mut mesh = quad(Texture::load(Path::new("assets/test.jpg")));
+    mesh.compute_bounds();
+
+    // the edges cross the sample grid at shallow angles, the texture checks that msaa keeps shading per pixel
+    let transform = Transform
+    {
+        translation: Vec3::new(0.0, 0.0, -3.0),
+        rotation: Quat::from_rotation_z(0.15) * Quat::from_rotation_y(0.6),
+        scale: Vec3::splat(1.2),
+    };
+
+    let camera = Camera::new(std::f32::consts::FRAC_PI_3, WIDTH as f32 / HEIGHT as f32, 0.5, 50.0);
+
+    assert_golden("antialiased_quad_msaa4", &render(&[(&mesh, transform)], &camera, DebugMode::Shaded, Antialiasing::Msaa(4)));
+    assert_golden("antialiased_quad_ssaa4", &render(&[(&mesh, transform)], &camera, DebugMode::Shaded, Antialiasing::Ssaa(4)));
+    assert_golden("antialiased_quad_depth", &render(&[(&mesh, transform)], &camera, DebugMode::Depth, Antialiasing::Msaa(4)));
+
+    for effect in PostEffect::ALL
+    {
+        let mut framebuffer = render(&[(&mesh, transform)], &camera, DebugMode::Shaded, Antialiasing::None);
+        let viewport = framebuffer.viewport();
+        PostChain::with_effects(&[effect]).apply(&mut framebuffer.color, viewport);
+
+        assert_golden(&format!("antialiased_quad_{}", effect.name()), &framebuffer);
+    }
+}
+
+#[test]
+#[ignore = "needs assets/DamagedHelmet.gltf"]
+fn helmet()
+{
+    let mut model = load_gltf_model(Path::new("assets/DamagedHelmet.gltf"));
+    model.mesh.texture = Some(Texture::load(Path::new("assets/helmet_albedo.jpg")));
+
+    let mut transform = Transform::from_translation(Vec3::new(0.0, 0.0, -5.0)).mul_transform(&model.transform);
+    transform.scale *= 1.5;
+
+    let camera = Camera::new(std::f32::consts::FRAC_PI_4, WIDTH as f32 / HEIGHT as f32, 1.0, 50.0);
+
+    assert_golden("helmet", &render(&[(&model.mesh, transform)], &camera, DebugMode::Shaded, Antialiasing::None));
+}
+
+fn quad(texture: Texture) -> Mesh
+{
+    let normal = Vec3::Z;
+
+    return Mesh
+    {
+        vertices: vec!
+        [
+            Vertex::new(Vec4::new(-1.0, -1.0, 0.0, 1.0), normal, Vec3::ONE, Vec2::new(0.0, 1.0)),
+            Vertex::new(Vec4::new(1.0, -1.0, 0.0, 1.0), normal, Vec3::ONE, Vec2::new(1.0, 1.0)),
+            Vertex::new(Vec4::new(-1.0, 1.0, 0.0, 1.0), normal, Vec3::ONE, Vec2::new(0.0, 0.0)),
+            Vertex::new(Vec4::new(1.0, 1.0, 0.0, 1.0), normal, Vec3::ONE, Vec2::new(1.0, 0.0)),
+        ],
+        indices: vec![UVec3::new(0, 1, 2), UVec3::new(1, 3, 2)],
+        texture: Some(texture),
+        ..Mesh::new()
+    };
+}
+
+fn render(meshes: &[(&Mesh, Transform)], camera: &Camera, mode: DebugMode, antialiasing: Antialiasing) -> Framebuffer
+{
+    let mut framebuffer = Framebuffer::with_hdr(WIDTH, HEIGHT);
+    framebuffer.set_antialiasing(antialiasing);
+
+    let mut renderer = Renderer::new(framebuffer);
+    renderer.debug.mode = mode;
+
+    renderer.begin_frame();
+    for (mesh, transform) in meshes
+    {
+        renderer.draw_mesh(mesh, transform, camera);
+    }
+    renderer.end_frame();
+
+    return renderer.framebuffer;
+}
+
+fn assert_golden(name: &str, framebuffer: &Framebuffer)
+{
+    let reference_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.ppm", name));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some()
+    {
+        std::fs::create_dir_all(reference_path.parent().unwrap()).unwrap();
+        framebuffer.save_ppm(&reference_path).unwrap();
+        eprintln!("wrote reference {}", reference_path.display());
+        return;
+    }
+
+    if !reference_path.exists()
+    {
+        panic!("{}: no reference at {}, check the render and write it with UPDATE_GOLDEN=1 cargo test --test golden", name, reference_path.display());
+    }
+
+    let (width, height, expected) = read_ppm(&reference_path);
+    assert_eq!((width, height), (framebuffer.width, framebuffer.height), "{}: reference has a different size", name);
+
+    let actual: Vec<[u8; 3]> = framebuffer.color.iter().map(|pixel| { let [_, r, g, b] = pixel.to_be_bytes(); [r, g, b] }).collect();
+
+    let mut different = 0;
+    let mut diff = Vec::with_capacity(actual.len());
+
+    for (a, e) in actual.iter().zip(expected.iter())
+    {
+        let error = (0..3).map(|c| a[c].abs_diff(e[c])).max().unwrap();
+
+        if error > PIXEL_TOLERANCE
+        {
+            different += 1;
+            diff.push(from_u8_rgba(255, 0, 0, 255));
+        }
+        else
+        {
+            // faded reference so the differences stand out
+            let gray = ((e[0] as u32 + e[1] as u32 + e[2] as u32) / 12) as u8;
+            diff.push(from_u8_rgba(gray, gray, gray, 255));
+        }
+    }
+
+    let allowed = (actual.len() as f32 * MAX_DIFFERENT_PIXELS) as usize;
+    if different > allowed
+    {
+        let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden");
+        std::fs::create_dir_all(&out_dir).unwrap();
+
+        let actual_path = out_dir.join(format!("{}_actual.ppm", name));
+        let diff_path = out_dir.join(format!("{}_diff.ppm", name));
+
+        framebuffer.save_ppm(&actual_path).unwrap();
+        let mut diff_image = Framebuffer::new(width, height);
+        diff_image.color = diff;
+        diff_image.save_ppm(&diff_path).unwrap();
+
+        panic!("{}: {} pixels differ from {} (at most {} allowed), see {} and {}", name, different, reference_path.display(), allowed, actual_path.display(), diff_path.display());
+    }
+}
+
+// only what Framebuffer::save_ppm writes: binary P6, 8 bits per channel
+fn read_ppm(path: &Path) -> (usize, usize, Vec<[u8; 3]>)
+{
+    let data = std::fs::read(path).unwrap();
+
+    let mut fields = Vec::new();
+    let mut start = 0;
+    let mut position = 0;
+
+    while fields.len() < 4
+    {
+        if data[position].is_ascii_whitespace()
+        {
+            if position > start
+            {
+                fields.push(std::str::from_utf8(&data[start..position]).unwrap().to_string());
+            }
+            start = position + 1;
+        }
+        position += 1;
+    }
+
+    assert_eq!(fields[0], "P6", "{} is not a binary ppm", path.display());
+    let width: usize = fields[1].parse().unwrap();
+    let height: usize = fields[2].parse().unwrap();
+
+    let pixels = data[position..].chunks_exact(3).map(|rgb| [rgb[0], rgb[1], rgb[2]]).collect::<Vec<_>>();
+    assert_eq!(pixels.len(), width * height, "{} is truncated", path.display());
+
+    return (width, height, pixels);
+}
@@ -0,0 +1,143 @@
+// the post-process passes against images whose ideal anti-aliased result is known
+
+use glam::Vec2;
+
+use rusterizer::*;
+
+const WIDTH: usize = 96;
+const HEIGHT: usize = 64;
+
+const WHITE: u32 = 0xffffffff;
+const BLACK: u32 = 0xff000000;
+
+// white below the line through `origin` with `slope`, sampled at pixel centers like the rasterizer
+fn half_plane(origin: Vec2, slope: f32) -> Vec<u32>
+{
+    return (0..WIDTH * HEIGHT).map(|p_i|
+    {
+        let (x, y) = ((p_i % WIDTH) as f32, (p_i / WIDTH) as f32);
+        if y > origin.y + (x - origin.x) * slope { WHITE } else { BLACK }
+    }).collect();
+}
+
+// fraction of each pixel's square below the line, supersampled
+fn coverage(origin: Vec2, slope: f32) -> Vec<f32>
+{
+    let n = 32;
+
+    return (0..WIDTH * HEIGHT).map(|p_i|
+    {
+        let (x, y) = ((p_i % WIDTH) as f32, (p_i / WIDTH) as f32);
+        let mut covered = 0;
+
+        for s_i in 0..n * n
+        {
+            let sx = x - 0.5 + ((s_i % n) as f32 + 0.5) / n as f32;
+            let sy = y - 0.5 + ((s_i / n) as f32 + 0.5) / n as f32;
+
+            if sy > origin.y + (sx - origin.x) * slope
+            {
+                covered += 1;
+            }
+        }
+
+        covered as f32 / (n * n) as f32
+    }).collect();
+}
+
+// a white pixel's share in linear light, which is what the passes blend
+fn intensity(pixel: u32) -> f32
+{
+    let [_, _, green, _] = pixel.to_be_bytes();
+
+    return srgb_to_linear(green as f32 / 255.0);
+}
+
+fn mean_error(image: &[u32], expected: &[f32]) -> f32
+{
+    return image.iter().zip(expected).map(|(pixel, coverage)| (intensity(*pixel) - coverage).abs()).sum::<f32>() / image.len() as f32;
+}
+
+fn apply(effects: &[PostEffect], image: &[u32]) -> Vec<u32>
+{
+    let mut result = image.to_vec();
+    PostChain::with_effects(effects).apply(&mut result, (WIDTH, HEIGHT));
+
+    return result;
+}
+
+#[test]
+fn flat_images_are_left_alone()
+{
+    // a gentle gradient stays below every edge threshold
+    let gradient: Vec<u32> = (0..WIDTH * HEIGHT).map(|p_i| (p_i % WIDTH * 2) as u8).map(|v| from_u8_rgba(v, v, v, 255)).collect();
+
+    for effect in PostEffect::ALL
+    {
+        assert_eq!(apply(&[effect], &vec![WHITE; WIDTH * HEIGHT]), vec![WHITE; WIDTH * HEIGHT], "{} changed a flat image", effect.name());
+        assert!(apply(&[effect], &gradient) == gradient, "{} changed a smooth gradient", effect.name());
+    }
+}
+
+#[test]
+fn axis_aligned_edges_stay_sharp()
+{
+    let image: Vec<u32> = (0..WIDTH * HEIGHT).map(|p_i| if p_i % WIDTH < WIDTH / 2 { BLACK } else { WHITE }).collect();
+
+    // without crossing edges there is no staircase to smooth
+    assert!(apply(&[PostEffect::Smaa], &image) == image, "smaa blurred a straight edge");
+}
+
+#[test]
+fn sloped_edges_move_towards_the_exact_coverage()
+{
+    // shallow and steep, rising and falling
+    for (origin, slope) in [(Vec2::new(0.0, 20.0), 0.15), (Vec2::new(10.0, 50.0), -0.4), (Vec2::new(40.0, 0.0), 1.0 / 0.15), (Vec2::new(60.0, 0.0), -2.5)]
+    {
+        let image = half_plane(origin, slope);
+        let expected = coverage(origin, slope);
+        let aliased = mean_error(&image, &expected);
+
+        for effects in [&[PostEffect::Fxaa][..], &[PostEffect::Smaa]]
+        {
+            let error = mean_error(&apply(effects, &image), &expected);
+            let name = PostChain::with_effects(effects).name();
+
+            assert!(error < aliased * 0.6, "{} on slope {}: error {} against {} without it", name, slope, error, aliased);
+        }
+    }
+}
+
+#[test]
+fn renderer_runs_the_chain_in_shaded_mode_only()
+{
+    let mut mesh = Mesh
+    {
+        vertices: [(-1.0, -1.0), (1.0, -0.8), (0.0, 1.0)].iter().map(|(x, y)| Vertex::new(glam::Vec4::new(*x, *y, -3.0, 1.0), glam::Vec3::Z, glam::Vec3::ONE, Vec2::ZERO)).collect(),
+        indices: vec![glam::UVec3::new(0, 1, 2)],
+        ..Mesh::new()
+    };
+    mesh.compute_bounds();
+
+    let camera = Camera::new(std::f32::consts::FRAC_PI_3, WIDTH as f32 / HEIGHT as f32, 0.5, 50.0);
+
+    let render = |mode: DebugMode, effects: &[PostEffect]|
+    {
+        let mut renderer = Renderer::new(Framebuffer::with_hdr(WIDTH, HEIGHT));
+        renderer.debug.mode = mode;
+        renderer.post_process = PostChain::with_effects(effects);
+
+        renderer.begin_frame();
+        renderer.draw_mesh(&mesh, &Transform::IDENTITY, &camera);
+        renderer.end_frame();
+
+        (renderer.framebuffer.color, renderer.stats)
+    };
+
+    let (plain, _) = render(DebugMode::Shaded, &[]);
+    let (smoothed, stats) = render(DebugMode::Shaded, &[PostEffect::Fxaa]);
+    assert!(plain != smoothed, "fxaa left the triangle's edges alone");
+    assert!(stats.timings.post > std::time::Duration::ZERO);
+
+    assert!(render(DebugMode::Barycentrics, &[]).0 == render(DebugMode::Barycentrics, &[PostEffect::Fxaa]).0, "fxaa touched a debug view");
+}
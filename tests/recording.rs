@@ -0,0 +1,46 @@
+// recordings survive a save and load, including keys that can't be named and the render size of every frame
+
+use glam::Vec2;
+use minifb::Key;
+use std::path::PathBuf;
+
+use rusterizer::*;
+
+#[test]
+fn recordings_round_trip_without_unknown_keys()
+{
+    let mut recording = InputRecording::new(1.0 / 60.0);
+    recording.push(&InputFrame { keys: vec![Key::W, Key::Unknown, Key::LeftShift], mouse_pos: Vec2::new(12.5, 40.0), mouse_buttons: [true, false, true], wheel: -1.5 }, (320, 180));
+    recording.push(&InputFrame { keys: vec![Key::Unknown], ..Default::default() }, (320, 180));
+    recording.push(&InputFrame::default(), (1280, 720));
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("round_trip.txt");
+    recording.save(&path).unwrap();
+    let loaded = InputRecording::load(&path).unwrap();
+
+    assert_eq!(loaded.timestep, recording.timestep);
+    assert_eq!(loaded.frames.len(), 3);
+    assert_eq!(loaded.render_sizes, vec![(320, 180), (320, 180), (1280, 720)]);
+
+    assert_eq!(loaded.frames[0].keys, vec![Key::W, Key::LeftShift]);
+    assert_eq!(loaded.frames[0].mouse_pos, Vec2::new(12.5, 40.0));
+    assert_eq!(loaded.frames[0].mouse_buttons, [true, false, true]);
+    assert_eq!(loaded.frames[0].wheel, -1.5);
+
+    assert!(loaded.frames[1].keys.is_empty());
+    assert!(loaded.frames[2].keys.is_empty());
+}
+
+#[test]
+fn version_1_recordings_load_without_render_sizes()
+{
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("version_1.txt");
+    std::fs::write(&path, "rusterizer-input 1\n0.016666668\n10 20 100 0 W,Up\n0 0 000 1 -\n").unwrap();
+
+    let loaded = InputRecording::load(&path).unwrap();
+
+    assert_eq!(loaded.frames.len(), 2);
+    assert!(loaded.render_sizes.is_empty());
+    assert_eq!(loaded.frames[0].keys, vec![Key::W, Key::Up]);
+    assert_eq!(loaded.frames[1].wheel, 1.0);
+}